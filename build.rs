@@ -0,0 +1,39 @@
+//! Digest of the crate sources, so cached output is invalidated when the
+//! exporters or template filters that render it change.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn main() {
+    println!("cargo:rerun-if-changed=src");
+    println!("cargo:rerun-if-changed=Cargo.toml");
+
+    let mut files = vec![PathBuf::from("Cargo.toml")];
+    collect_files(Path::new("src"), &mut files);
+    files.sort();
+
+    // 64-bit FNV-1a, matching the generator's content hashes
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for file in &files {
+        let content = fs::read(file).unwrap_or_default();
+        for byte in file.to_string_lossy().bytes().chain(content) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    println!("cargo:rustc-env=AKARI_SOURCE_DIGEST={hash:016x}");
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_files(&path, files);
+        } else {
+            files.push(path);
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! Adobe Swatch Exchange (`.ase`) palettes for Illustrator, Photoshop and Figma plugins.
+
+use crate::Palette;
+use crate::exporter::swatch_groups;
+
+const GROUP_START: u16 = 0xC001;
+const GROUP_END: u16 = 0xC002;
+const COLOR_ENTRY: u16 = 0x0001;
+/// Color type "global" (as opposed to spot or normal)
+const GLOBAL: u16 = 0;
+
+/// Generate an ASE file with one named group per palette section.
+///
+/// ASE stores opaque RGB, so alpha is dropped.
+#[must_use]
+pub fn generate(palette: &Palette) -> Vec<u8> {
+    let mut blocks = Vec::new();
+    let mut count: u32 = 0;
+
+    for (section, colors) in swatch_groups(palette) {
+        push_block(&mut blocks, GROUP_START, &name_bytes(&section));
+        count += 1;
+
+        for (key, color) in colors {
+            let mut body = name_bytes(&key);
+            body.extend_from_slice(b"RGB ");
+            let (r, g, b) = color.rgb().as_floats();
+            for component in [r, g, b] {
+                body.extend_from_slice(&(component as f32).to_be_bytes());
+            }
+            body.extend_from_slice(&GLOBAL.to_be_bytes());
+            push_block(&mut blocks, COLOR_ENTRY, &body);
+            count += 1;
+        }
+
+        push_block(&mut blocks, GROUP_END, &[]);
+        count += 1;
+    }
+
+    let mut out = Vec::with_capacity(12 + blocks.len());
+    out.extend_from_slice(b"ASEF");
+    out.extend_from_slice(&1u16.to_be_bytes());
+    out.extend_from_slice(&0u16.to_be_bytes());
+    out.extend_from_slice(&count.to_be_bytes());
+    out.extend_from_slice(&blocks);
+    out
+}
+
+fn push_block(out: &mut Vec<u8>, kind: u16, body: &[u8]) {
+    let len = u32::try_from(body.len()).expect("ASE block fits in u32");
+    out.extend_from_slice(&kind.to_be_bytes());
+    out.extend_from_slice(&len.to_be_bytes());
+    out.extend_from_slice(body);
+}
+
+/// Name as a UTF-16BE string prefixed with its length, including the trailing NUL.
+fn name_bytes(name: &str) -> Vec<u8> {
+    let units: Vec<u16> = name.encode_utf16().chain([0]).collect();
+    let len = u16::try_from(units.len()).expect("ASE name fits in u16");
+    let mut out = len.to_be_bytes().to_vec();
+    for unit in units {
+        out.extend_from_slice(&unit.to_be_bytes());
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Minimal reader returning (group, name, rgb) for each color entry.
+    fn parse(data: &[u8]) -> Vec<(String, String, [f32; 3])> {
+        let u16_at = |i: usize| u16::from_be_bytes([data[i], data[i + 1]]);
+        let u32_at = |i: usize| u32::from_be_bytes(data[i..i + 4].try_into().unwrap());
+        let name_at = |i: usize| {
+            let len = usize::from(u16_at(i));
+            let units: Vec<u16> = (0..len - 1).map(|n| u16_at(i + 2 + n * 2)).collect();
+            (String::from_utf16(&units).unwrap(), i + 2 + len * 2)
+        };
+
+        assert_eq!(&data[..4], b"ASEF");
+        let count = u32_at(8) as usize;
+        let mut entries = Vec::new();
+        let mut group = String::new();
+        let mut pos = 12;
+        for _ in 0..count {
+            let kind = u16_at(pos);
+            let len = u32_at(pos + 2) as usize;
+            let body = pos + 6;
+            match kind {
+                GROUP_START => group = name_at(body).0,
+                GROUP_END => group.clear(),
+                COLOR_ENTRY => {
+                    let (name, at) = name_at(body);
+                    assert_eq!(&data[at..at + 4], b"RGB ");
+                    let f = |n: usize| {
+                        f32::from_be_bytes(data[at + 4 + n * 4..][..4].try_into().unwrap())
+                    };
+                    entries.push((group.clone(), name, [f(0), f(1), f(2)]));
+                }
+                other => panic!("unexpected block type {other:#x}"),
+            }
+            pos = body + len;
+        }
+        assert_eq!(pos, data.len());
+        entries
+    }
+
+    #[test]
+    fn generate_groups_every_color() {
+        let palette = Palette::night();
+        let entries = parse(&generate(&palette));
+        assert_eq!(entries.len(), palette.iter().count());
+
+        let groups: Vec<&str> = entries.iter().map(|(g, ..)| g.as_str()).collect();
+        for section in ["colors", "base", "layers", "state", "semantic", "ansi"] {
+            assert!(groups.contains(&section), "missing group {section}");
+        }
+
+        let (_, _, rgb) = entries
+            .iter()
+            .find(|(group, name, _)| group == "ansi" && name == "bright.red")
+            .unwrap();
+        let (r, g, b) = palette.ansi_bright.red.rgb().as_floats();
+        assert_eq!(*rgb, [r as f32, g as f32, b as f32]);
+    }
+
+    #[test]
+    fn names_are_utf16_with_nul() {
+        assert_eq!(name_bytes("ab"), [0, 3, 0, b'a', 0, b'b', 0, 0]);
+    }
+}
@@ -1,7 +1,11 @@
 use crate::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt;
 use std::str::FromStr;
 
+/// Chroma below which an OKLCH color is treated as gray (hue undefined).
+const ACHROMATIC: f64 = 1e-4;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Rgb {
     pub r: u8,
@@ -30,7 +34,206 @@ impl FromStr for Rgb {
 
 impl fmt::Display for Rgb {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "#{:02X}{:02X}{:02X}", self.r, self.g, self.b)
+        f.pad(&format!("#{:02X}{:02X}{:02X}", self.r, self.g, self.b))
+    }
+}
+
+/// Serializes as a `#RRGGBB` hex string.
+impl Serialize for Rgb {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Rgb {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// An RGB color with an alpha channel.
+///
+/// Parses both `#RRGGBB` (opaque) and `#RRGGBBAA`, and displays as `#RRGGBB`
+/// when fully opaque so that opaque colors round-trip unchanged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rgba {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl FromStr for Rgba {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex = s.strip_prefix('#').unwrap_or(s);
+
+        if !hex.is_ascii() || (hex.len() != 6 && hex.len() != 8) {
+            return Err(Error::InvalidHex(hex.to_string()));
+        }
+
+        let rgb: Rgb = hex[..6].parse()?;
+        let a = match hex.get(6..8) {
+            Some(alpha) => {
+                u8::from_str_radix(alpha, 16).map_err(|_| Error::InvalidHex(hex.to_string()))?
+            }
+            None => u8::MAX,
+        };
+
+        Ok(rgb.with_alpha_u8(a))
+    }
+}
+
+impl fmt::Display for Rgba {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut hex = self.rgb().to_string();
+        if !self.is_opaque() {
+            hex.push_str(&format!("{:02X}", self.a));
+        }
+        f.pad(&hex)
+    }
+}
+
+/// Serializes as a hex string, `#RRGGBB` when opaque and `#RRGGBBAA` otherwise.
+impl Serialize for Rgba {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Rgba {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+impl From<Rgb> for Rgba {
+    fn from(rgb: Rgb) -> Self {
+        rgb.with_alpha_u8(u8::MAX)
+    }
+}
+
+impl Rgba {
+    /// Returns the color without its alpha channel.
+    #[must_use]
+    pub const fn rgb(self) -> Rgb {
+        Rgb {
+            r: self.r,
+            g: self.g,
+            b: self.b,
+        }
+    }
+
+    /// Returns the alpha channel in 0.0-1.0 range.
+    #[must_use]
+    pub const fn alpha(self) -> f64 {
+        self.a as f64 / 255.0
+    }
+
+    #[must_use]
+    pub const fn is_opaque(self) -> bool {
+        self.a == u8::MAX
+    }
+
+    /// Replace the alpha channel.
+    ///
+    /// `alpha` of 0.0 is fully transparent, 1.0 is fully opaque.
+    #[must_use]
+    pub fn with_alpha(self, alpha: f64) -> Self {
+        self.rgb().with_alpha(alpha)
+    }
+
+    /// Apply an RGB operation while preserving the alpha channel.
+    #[must_use]
+    pub fn map_rgb(self, f: impl FnOnce(Rgb) -> Rgb) -> Self {
+        f(self.rgb()).with_alpha_u8(self.a)
+    }
+
+    /// Mix two colors together, including their alpha channels.
+    ///
+    /// `factor` of 0.0 returns self, 1.0 returns other.
+    #[must_use]
+    pub fn mix(self, other: Self, factor: f64) -> Self {
+        let factor = factor.clamp(0.0, 1.0);
+        self.rgb()
+            .mix(other.rgb(), factor)
+            .with_alpha_u8(Rgb::blend_channel(self.a, other.a, factor))
+    }
+
+    /// Mix two colors in OKLCH space, including their alpha channels.
+    ///
+    /// `factor` of 0.0 returns self, 1.0 returns other.
+    #[must_use]
+    pub fn oklch_mix(self, other: Self, factor: f64) -> Self {
+        let factor = factor.clamp(0.0, 1.0);
+        self.rgb()
+            .oklch_mix(other.rgb(), factor)
+            .with_alpha_u8(Rgb::blend_channel(self.a, other.a, factor))
+    }
+
+    /// Composite the color over an opaque background.
+    #[must_use]
+    pub fn over(self, background: Rgb) -> Rgb {
+        background.mix(self.rgb(), self.alpha())
+    }
+
+    /// Returns [r, g, b, a] as f32 values in 0.0-1.0 range.
+    ///
+    /// Useful for GPU APIs like wgpu that expect f32 RGBA colors.
+    #[must_use]
+    pub const fn to_array(self) -> [f32; 4] {
+        [
+            self.r as f32 / 255.0,
+            self.g as f32 / 255.0,
+            self.b as f32 / 255.0,
+            self.a as f32 / 255.0,
+        ]
+    }
+}
+
+/// A color in the OKLab perceptual color space.
+///
+/// `l` is perceived lightness (0.0 to 1.0), `a` and `b` are the green-red and
+/// blue-yellow opponent axes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Oklab {
+    pub l: f64,
+    pub a: f64,
+    pub b: f64,
+}
+
+/// A color in OKLCH, the polar form of [`Oklab`].
+///
+/// `l` is perceived lightness (0.0 to 1.0), `c` is chroma and `h` is hue in
+/// degrees (0.0 to 360.0).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Oklch {
+    pub l: f64,
+    pub c: f64,
+    pub h: f64,
+}
+
+impl From<Oklab> for Oklch {
+    fn from(lab: Oklab) -> Self {
+        let c = lab.a.hypot(lab.b);
+        let h = lab.b.atan2(lab.a).to_degrees().rem_euclid(360.0);
+        Self { l: lab.l, c, h }
+    }
+}
+
+impl From<Oklch> for Oklab {
+    fn from(lch: Oklch) -> Self {
+        let h = lch.h.to_radians();
+        Self {
+            l: lch.l,
+            a: lch.c * h.cos(),
+            b: lch.c * h.sin(),
+        }
     }
 }
 
@@ -69,6 +272,23 @@ impl Rgb {
         ]
     }
 
+    /// Attach an alpha channel to the color.
+    ///
+    /// `alpha` of 0.0 is fully transparent, 1.0 is fully opaque.
+    #[must_use]
+    pub fn with_alpha(self, alpha: f64) -> Rgba {
+        self.with_alpha_u8((alpha.clamp(0.0, 1.0) * 255.0).round() as u8)
+    }
+
+    const fn with_alpha_u8(self, a: u8) -> Rgba {
+        Rgba {
+            r: self.r,
+            g: self.g,
+            b: self.b,
+            a,
+        }
+    }
+
     #[must_use]
     pub fn to_array_string(self) -> String {
         format!("[{}, {}, {}]", self.r, self.g, self.b)
@@ -130,6 +350,289 @@ impl Rgb {
         }
     }
 
+    /// Lighten the color by increasing perceived lightness in OKLCH space.
+    ///
+    /// Like [`Rgb::lighten`], but hue and chroma stay perceptually constant.
+    /// `factor` of 0.0 returns the original color, 1.0 returns white.
+    #[must_use]
+    pub fn oklch_lighten(self, factor: f64) -> Self {
+        let factor = factor.clamp(0.0, 1.0);
+        let lch = self.to_oklch();
+        Self::from_oklch(Oklch {
+            l: lch.l + (1.0 - lch.l) * factor,
+            ..lch
+        })
+    }
+
+    /// Darken the color by decreasing perceived lightness in OKLCH space.
+    ///
+    /// `factor` of 0.0 returns the original color, 1.0 returns black.
+    #[must_use]
+    pub fn oklch_darken(self, factor: f64) -> Self {
+        let factor = factor.clamp(0.0, 1.0);
+        let lch = self.to_oklch();
+        Self::from_oklch(Oklch {
+            l: lch.l * (1.0 - factor),
+            ..lch
+        })
+    }
+
+    /// Mix two colors in OKLCH space, taking the shorter way around the hue circle.
+    ///
+    /// `factor` of 0.0 returns self, 1.0 returns other.
+    #[must_use]
+    pub fn oklch_mix(self, other: Self, factor: f64) -> Self {
+        let factor = factor.clamp(0.0, 1.0);
+        let from = self.to_oklch();
+        let to = other.to_oklch();
+
+        // Achromatic colors have no meaningful hue; borrow the other one's
+        let (from_h, to_h) = match (from.c < ACHROMATIC, to.c < ACHROMATIC) {
+            (true, false) => (to.h, to.h),
+            (false, true) => (from.h, from.h),
+            _ => (from.h, to.h),
+        };
+        let dh = (to_h - from_h + 180.0).rem_euclid(360.0) - 180.0;
+
+        Self::from_oklch(Oklch {
+            l: from.l + (to.l - from.l) * factor,
+            c: from.c + (to.c - from.c) * factor,
+            h: from_h + dh * factor,
+        })
+    }
+
+    /// Scale chroma in OKLCH space, keeping perceived lightness and hue.
+    ///
+    /// `factor` of 0.0 returns a gray of the same lightness, 1.0 returns the
+    /// original color, and values above 1.0 saturate (up to the sRGB gamut).
+    #[must_use]
+    pub fn chroma(self, factor: f64) -> Self {
+        let lch = self.to_oklch();
+        Self::from_oklch(Oklch {
+            c: lch.c * factor.max(0.0),
+            ..lch
+        })
+    }
+
+    /// Relative luminance as defined by WCAG 2.x (0.0 = black, 1.0 = white).
+    #[must_use]
+    pub fn relative_luminance(self) -> f64 {
+        let (r, g, b) = self.to_linear();
+        0.2126 * r + 0.7152 * g + 0.0722 * b
+    }
+
+    /// WCAG 2.x contrast ratio between two colors (1.0 to 21.0).
+    ///
+    /// The ratio is symmetric, so the order of the colors does not matter.
+    #[must_use]
+    pub fn contrast_ratio(self, other: Self) -> f64 {
+        let a = self.relative_luminance();
+        let b = other.relative_luminance();
+        (a.max(b) + 0.05) / (a.min(b) + 0.05)
+    }
+
+    /// Adjust perceived lightness by the smallest amount that reaches `min_ratio`
+    /// WCAG contrast against `background`.
+    ///
+    /// Hue and chroma are kept in OKLCH space. Returns the color unchanged if it
+    /// already passes; if no lightness reaches the target, returns the
+    /// best-contrasting lightness.
+    #[must_use]
+    pub fn ensure_contrast(self, background: Self, min_ratio: f64) -> Self {
+        if self.contrast_ratio(background) >= min_ratio {
+            return self;
+        }
+
+        let lch = self.to_oklch();
+        let at = |l: f64| Self::from_oklch(Oklch { l, ..lch });
+        let passes = |l: f64| at(l).contrast_ratio(background) >= min_ratio;
+
+        // Search each direction for the smallest lightness change that passes
+        let search = |limit: f64| -> Option<f64> {
+            if !passes(limit) {
+                return None;
+            }
+            let (mut near, mut far) = (lch.l, limit);
+            for _ in 0..32 {
+                let mid = (near + far) / 2.0;
+                if passes(mid) {
+                    far = mid;
+                } else {
+                    near = mid;
+                }
+            }
+            Some(far)
+        };
+
+        match (search(1.0), search(0.0)) {
+            (Some(up), Some(down)) if up - lch.l <= lch.l - down => at(up),
+            (_, Some(down)) => at(down),
+            (Some(up), None) => at(up),
+            (None, None) => {
+                let (light, dark) = (at(1.0), at(0.0));
+                if light.contrast_ratio(background) >= dark.contrast_ratio(background) {
+                    light
+                } else {
+                    dark
+                }
+            }
+        }
+    }
+
+    /// APCA lightness contrast (Lc) of `self` as text on `background`.
+    ///
+    /// Implements APCA 0.0.98G-4g. Positive values mean dark text on a light
+    /// background, negative values light text on a dark background; compare
+    /// the absolute value against thresholds such as 60 (body text) or 75.
+    #[must_use]
+    pub fn apca_contrast(self, background: Self) -> f64 {
+        let text_y = self.apca_luminance();
+        let bg_y = background.apca_luminance();
+
+        if (bg_y - text_y).abs() < 0.0005 {
+            return 0.0;
+        }
+
+        let lc = if bg_y > text_y {
+            let sapc = (bg_y.powf(0.56) - text_y.powf(0.57)) * 1.14;
+            if sapc < 0.1 { 0.0 } else { sapc - 0.027 }
+        } else {
+            let sapc = (bg_y.powf(0.65) - text_y.powf(0.62)) * 1.14;
+            if sapc > -0.1 { 0.0 } else { sapc + 0.027 }
+        };
+
+        lc * 100.0
+    }
+
+    /// Screen luminance estimate used by APCA, with the soft black clamp applied.
+    fn apca_luminance(self) -> f64 {
+        let (r, g, b) = self.as_floats();
+        let y = 0.212_672_9 * r.powf(2.4) + 0.715_152_2 * g.powf(2.4) + 0.072_175 * b.powf(2.4);
+        if y < 0.022 {
+            y + (0.022 - y).powf(1.414)
+        } else {
+            y
+        }
+    }
+
+    /// Convert to OKLab.
+    #[must_use]
+    pub fn to_oklab(self) -> Oklab {
+        let (r, g, b) = self.to_linear();
+
+        let l = (0.412_221_470_8 * r + 0.536_332_536_3 * g + 0.051_445_992_9 * b).cbrt();
+        let m = (0.211_903_498_2 * r + 0.680_699_545_1 * g + 0.107_396_956_6 * b).cbrt();
+        let s = (0.088_302_461_9 * r + 0.281_718_837_6 * g + 0.629_978_700_5 * b).cbrt();
+
+        Oklab {
+            l: 0.210_454_255_3 * l + 0.793_617_785_0 * m - 0.004_072_046_8 * s,
+            a: 1.977_998_495_1 * l - 2.428_592_205_0 * m + 0.450_593_709_9 * s,
+            b: 0.025_904_037_1 * l + 0.782_771_766_2 * m - 0.808_675_766_0 * s,
+        }
+    }
+
+    /// Convert from OKLab, clamping out-of-gamut channels.
+    #[must_use]
+    pub fn from_oklab(lab: Oklab) -> Self {
+        let (r, g, b) = Self::oklab_to_linear(lab);
+        Self::from_linear(r, g, b)
+    }
+
+    /// Perceptual distance ΔE_OK: Euclidean distance in OKLab.
+    ///
+    /// 0.0 for identical colors, about 0.02 for a just-noticeable difference,
+    /// 1.0 between black and white.
+    #[must_use]
+    pub fn delta_e(self, other: Self) -> f64 {
+        let (x, y) = (self.to_oklab(), other.to_oklab());
+        ((x.l - y.l).powi(2) + (x.a - y.a).powi(2) + (x.b - y.b).powi(2)).sqrt()
+    }
+
+    /// Convert to OKLCH.
+    #[must_use]
+    pub fn to_oklch(self) -> Oklch {
+        self.to_oklab().into()
+    }
+
+    /// Convert from OKLCH.
+    ///
+    /// Out-of-gamut colors are mapped into sRGB by reducing chroma, which keeps
+    /// lightness and hue intact.
+    #[must_use]
+    pub fn from_oklch(lch: Oklch) -> Self {
+        let lch = Oklch {
+            l: lch.l.clamp(0.0, 1.0),
+            ..lch
+        };
+        if Self::in_gamut(lch) {
+            return Self::from_oklab(lch.into());
+        }
+
+        let (mut lo, mut hi) = (0.0, lch.c);
+        while hi - lo > 1e-4 {
+            let mid = (lo + hi) / 2.0;
+            if Self::in_gamut(Oklch { c: mid, ..lch }) {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        Self::from_oklab(Oklch { c: lo, ..lch }.into())
+    }
+
+    fn in_gamut(lch: Oklch) -> bool {
+        const EPS: f64 = 1e-6;
+        let (r, g, b) = Self::oklab_to_linear(lch.into());
+        [r, g, b].iter().all(|v| (-EPS..=1.0 + EPS).contains(v))
+    }
+
+    fn oklab_to_linear(lab: Oklab) -> (f64, f64, f64) {
+        let l = (lab.l + 0.396_337_777_4 * lab.a + 0.215_803_757_3 * lab.b).powi(3);
+        let m = (lab.l - 0.105_561_345_8 * lab.a - 0.063_854_172_8 * lab.b).powi(3);
+        let s = (lab.l - 0.089_484_177_5 * lab.a - 1.291_485_548_0 * lab.b).powi(3);
+
+        (
+            4.076_741_662_1 * l - 3.307_711_591_3 * m + 0.230_969_929_2 * s,
+            -1.268_438_004_6 * l + 2.609_757_401_1 * m - 0.341_319_396_5 * s,
+            -0.004_196_086_3 * l - 0.703_418_614_7 * m + 1.707_614_701_0 * s,
+        )
+    }
+
+    /// Returns linear-light sRGB channels in 0.0-1.0 range.
+    fn to_linear(self) -> (f64, f64, f64) {
+        let (r, g, b) = self.as_floats();
+        (
+            Self::srgb_to_linear(r),
+            Self::srgb_to_linear(g),
+            Self::srgb_to_linear(b),
+        )
+    }
+
+    fn from_linear(r: f64, g: f64, b: f64) -> Self {
+        let encode = |c: f64| (Self::linear_to_srgb(c.clamp(0.0, 1.0)) * 255.0).round() as u8;
+        Self {
+            r: encode(r),
+            g: encode(g),
+            b: encode(b),
+        }
+    }
+
+    fn srgb_to_linear(c: f64) -> f64 {
+        if c <= 0.040_45 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    }
+
+    fn linear_to_srgb(c: f64) -> f64 {
+        if c <= 0.003_130_8 {
+            c * 12.92
+        } else {
+            1.055 * c.powf(1.0 / 2.4) - 0.055
+        }
+    }
+
     /// Convert RGB to HSL.
     ///
     /// Returns (hue, saturation, lightness) where:
@@ -621,4 +1124,328 @@ mod tests {
         assert!(approx_eq_f32(arr[2], 1.0));
         assert!(approx_eq_f32(arr[3], 1.0));
     }
+
+    #[test]
+    fn rgba_parse_opaque() {
+        let rgba: Rgba = "#E26A3B".parse().unwrap();
+        assert_eq!(
+            rgba,
+            Rgba {
+                r: 226,
+                g: 106,
+                b: 59,
+                a: 255
+            }
+        );
+    }
+
+    #[test]
+    fn rgba_parse_with_alpha() {
+        let rgba: Rgba = "#E26A3B80".parse().unwrap();
+        assert_eq!(rgba.rgb(), "#E26A3B".parse::<Rgb>().unwrap());
+        assert_eq!(rgba.a, 0x80);
+    }
+
+    #[test]
+    fn rgba_parse_invalid() {
+        assert!("#E26A3B8".parse::<Rgba>().is_err());
+        assert!("#E26A3BGG".parse::<Rgba>().is_err());
+        assert!("#E26A3B8000".parse::<Rgba>().is_err());
+    }
+
+    #[test]
+    fn rgba_display_omits_opaque_alpha() {
+        let rgba: Rgba = "#e26a3bff".parse().unwrap();
+        assert_eq!(rgba.to_string(), "#E26A3B");
+    }
+
+    #[test]
+    fn rgba_display_includes_alpha() {
+        let rgba: Rgba = "#e26a3b40".parse().unwrap();
+        assert_eq!(rgba.to_string(), "#E26A3B40");
+    }
+
+    #[test]
+    fn rgba_display_pads() {
+        let opaque: Rgba = "#e26a3b".parse().unwrap();
+        let translucent: Rgba = "#e26a3b40".parse().unwrap();
+        assert_eq!(format!("{opaque:<9}|"), "#E26A3B  |");
+        assert_eq!(format!("{translucent:<9}|"), "#E26A3B40|");
+        assert_eq!(format!("{:>8}", opaque.rgb()), " #E26A3B");
+    }
+
+    #[test]
+    fn delta_e_is_symmetric_distance() {
+        let black = Rgb { r: 0, g: 0, b: 0 };
+        let white = Rgb {
+            r: 255,
+            g: 255,
+            b: 255,
+        };
+        let ember: Rgb = "#D65A3A".parse().unwrap();
+        let near: Rgb = "#D25046".parse().unwrap();
+
+        assert!(approx_eq(black.delta_e(black), 0.0));
+        assert!(approx_eq(black.delta_e(white), 1.0));
+        assert!(approx_eq(ember.delta_e(near), near.delta_e(ember)));
+        assert!(ember.delta_e(near) < 0.1);
+    }
+
+    #[test]
+    fn rgba_serde_round_trips_as_hex() {
+        #[derive(Debug, PartialEq, Deserialize, Serialize)]
+        struct Entry {
+            opaque: Rgba,
+            translucent: Rgba,
+        }
+
+        let entry: Entry =
+            toml::from_str("opaque = \"#e26a3b\"\ntranslucent = \"#E26A3B40\"").unwrap();
+        assert!(entry.opaque.is_opaque());
+        assert_eq!(entry.translucent.a, 0x40);
+
+        let serialized = toml::to_string(&entry).unwrap();
+        assert_eq!(
+            serialized,
+            "opaque = \"#E26A3B\"\ntranslucent = \"#E26A3B40\"\n"
+        );
+        assert!(toml::from_str::<Entry>("opaque = \"#GG0000\"\ntranslucent = \"#000\"").is_err());
+    }
+
+    #[test]
+    fn with_alpha_rounds_to_byte() {
+        let rgb = Rgb {
+            r: 226,
+            g: 106,
+            b: 59,
+        };
+        assert_eq!(rgb.with_alpha(0.5).a, 0x80);
+        assert_eq!(rgb.with_alpha(0.25).a, 0x40);
+        assert_eq!(rgb.with_alpha(0.125).a, 0x20);
+        assert_eq!(rgb.with_alpha(2.0).a, 0xFF);
+        assert_eq!(rgb.with_alpha(-1.0).a, 0x00);
+    }
+
+    #[test]
+    fn rgba_mix_blends_alpha() {
+        let a: Rgba = "#00000000".parse().unwrap();
+        let b: Rgba = "#FFFFFFFF".parse().unwrap();
+        assert_eq!(a.mix(b, 0.5).to_string(), "#80808080");
+    }
+
+    #[test]
+    fn rgba_map_rgb_preserves_alpha() {
+        let rgba: Rgba = "#64646480".parse().unwrap();
+        let darker = rgba.map_rgb(|c| c.darken(0.5));
+        assert_eq!(darker.to_string(), "#32323280");
+    }
+
+    #[test]
+    fn oklab_red_reference_values() {
+        let lab = Rgb { r: 255, g: 0, b: 0 }.to_oklab();
+        assert!(approx_eq(lab.l, 0.627_96));
+        assert!(approx_eq(lab.a, 0.224_86));
+        assert!(approx_eq(lab.b, 0.125_85));
+    }
+
+    #[test]
+    fn oklab_white_and_black() {
+        let white = Rgb {
+            r: 255,
+            g: 255,
+            b: 255,
+        }
+        .to_oklch();
+        assert!(approx_eq(white.l, 1.0));
+        assert!(white.c < 0.001);
+
+        let black = Rgb { r: 0, g: 0, b: 0 }.to_oklch();
+        assert!(approx_eq(black.l, 0.0));
+    }
+
+    #[test]
+    fn oklab_round_trip() {
+        for hex in ["#E26A3B", "#7A8FA2", "#25231F", "#E6DED3", "#7FAF6A"] {
+            let rgb: Rgb = hex.parse().unwrap();
+            assert_eq!(Rgb::from_oklab(rgb.to_oklab()), rgb);
+            assert_eq!(Rgb::from_oklch(rgb.to_oklch()), rgb);
+        }
+    }
+
+    #[test]
+    fn oklch_lighten_bounds() {
+        let rgb: Rgb = "#7A8FA2".parse().unwrap();
+        assert_eq!(rgb.oklch_lighten(0.0), rgb);
+        assert_eq!(
+            rgb.oklch_lighten(1.0),
+            Rgb {
+                r: 255,
+                g: 255,
+                b: 255
+            }
+        );
+    }
+
+    #[test]
+    fn oklch_lighten_preserves_hue() {
+        let rgb: Rgb = "#E26A3B".parse().unwrap();
+        let before = rgb.to_oklch();
+        let after = rgb.oklch_lighten(0.2).to_oklch();
+        assert!(after.l > before.l);
+        assert!((after.h - before.h).abs() < 2.0);
+    }
+
+    #[test]
+    fn oklch_darken_bounds() {
+        let rgb: Rgb = "#E26A3B".parse().unwrap();
+        assert_eq!(rgb.oklch_darken(0.0), rgb);
+        assert_eq!(rgb.oklch_darken(1.0), Rgb { r: 0, g: 0, b: 0 });
+    }
+
+    #[test]
+    fn oklch_mix_endpoints() {
+        let a: Rgb = "#25231F".parse().unwrap();
+        let b: Rgb = "#7A8FA2".parse().unwrap();
+        assert_eq!(a.oklch_mix(b, 0.0), a);
+        assert_eq!(a.oklch_mix(b, 1.0), b);
+    }
+
+    #[test]
+    fn oklch_mix_lightness_is_linear() {
+        let black = Rgb { r: 0, g: 0, b: 0 };
+        let white = Rgb {
+            r: 255,
+            g: 255,
+            b: 255,
+        };
+        let mid = black.oklch_mix(white, 0.5).to_oklch();
+        assert!((mid.l - 0.5).abs() < 0.01);
+    }
+
+    #[test]
+    fn chroma_zero_is_gray() {
+        let gray = "#E26A3B".parse::<Rgb>().unwrap().chroma(0.0);
+        assert!(gray.r.abs_diff(gray.g) <= 1);
+        assert!(gray.g.abs_diff(gray.b) <= 1);
+    }
+
+    #[test]
+    fn chroma_one_unchanged() {
+        let rgb: Rgb = "#7FAF6A".parse().unwrap();
+        assert_eq!(rgb.chroma(1.0), rgb);
+    }
+
+    #[test]
+    fn from_oklch_maps_out_of_gamut() {
+        // Far outside sRGB: must still produce a color with the requested lightness
+        let rgb = Rgb::from_oklch(Oklch {
+            l: 0.7,
+            c: 0.5,
+            h: 40.0,
+        });
+        assert!((rgb.to_oklch().l - 0.7).abs() < 0.01);
+    }
+
+    #[test]
+    fn relative_luminance_bounds() {
+        let black = Rgb { r: 0, g: 0, b: 0 };
+        let white = Rgb {
+            r: 255,
+            g: 255,
+            b: 255,
+        };
+        assert!(approx_eq(black.relative_luminance(), 0.0));
+        assert!(approx_eq(white.relative_luminance(), 1.0));
+    }
+
+    #[test]
+    fn contrast_ratio_black_white() {
+        let black = Rgb { r: 0, g: 0, b: 0 };
+        let white = Rgb {
+            r: 255,
+            g: 255,
+            b: 255,
+        };
+        assert!(approx_eq(black.contrast_ratio(white), 21.0));
+        assert!(approx_eq(white.contrast_ratio(black), 21.0));
+        assert!(approx_eq(white.contrast_ratio(white), 1.0));
+    }
+
+    #[test]
+    fn contrast_ratio_reference_value() {
+        // #767676 on white is the classic "just passes AA" gray
+        let gray: Rgb = "#767676".parse().unwrap();
+        let white: Rgb = "#FFFFFF".parse().unwrap();
+        assert!((gray.contrast_ratio(white) - 4.54).abs() < 0.01);
+    }
+
+    #[test]
+    fn apca_reference_values() {
+        let black = Rgb { r: 0, g: 0, b: 0 };
+        let white = Rgb {
+            r: 255,
+            g: 255,
+            b: 255,
+        };
+        let gray: Rgb = "#888888".parse().unwrap();
+        assert!((black.apca_contrast(white) - 106.04).abs() < 0.1);
+        assert!((white.apca_contrast(black) + 107.88).abs() < 0.1);
+        assert!((gray.apca_contrast(white) - 63.06).abs() < 0.1);
+    }
+
+    #[test]
+    fn apca_same_color_is_zero() {
+        let gray: Rgb = "#888888".parse().unwrap();
+        assert!(approx_eq(gray.apca_contrast(gray), 0.0));
+    }
+
+    #[test]
+    fn rgba_over_composites() {
+        let fg: Rgba = "#FFFFFF80".parse().unwrap();
+        let bg = Rgb { r: 0, g: 0, b: 0 };
+        assert_eq!(fg.over(bg).to_string(), "#808080");
+    }
+
+    #[test]
+    fn ensure_contrast_keeps_passing_color() {
+        let fg: Rgb = "#E6DED3".parse().unwrap();
+        let bg: Rgb = "#25231F".parse().unwrap();
+        assert_eq!(fg.ensure_contrast(bg, 4.5), fg);
+    }
+
+    #[test]
+    fn ensure_contrast_reaches_target_on_dark() {
+        let fg: Rgb = "#8E7BA0".parse().unwrap();
+        let bg: Rgb = "#25231F".parse().unwrap();
+        let adjusted = fg.ensure_contrast(bg, 7.0);
+        assert!(adjusted.contrast_ratio(bg) >= 7.0);
+        // Lightened, not darkened, against a dark background
+        assert!(adjusted.to_oklch().l > fg.to_oklch().l);
+        // Minimal change: only just over the target
+        assert!(adjusted.contrast_ratio(bg) < 7.2);
+    }
+
+    #[test]
+    fn ensure_contrast_reaches_target_on_light() {
+        let fg: Rgb = "#D4A05A".parse().unwrap();
+        let bg: Rgb = "#F5EFE6".parse().unwrap();
+        let adjusted = fg.ensure_contrast(bg, 4.5);
+        assert!(adjusted.contrast_ratio(bg) >= 4.5);
+        assert!(adjusted.to_oklch().l < fg.to_oklch().l);
+    }
+
+    #[test]
+    fn ensure_contrast_preserves_hue() {
+        let fg: Rgb = "#E26A3B".parse().unwrap();
+        let bg: Rgb = "#F5EFE6".parse().unwrap();
+        let adjusted = fg.ensure_contrast(bg, 4.5);
+        assert!((adjusted.to_oklch().h - fg.to_oklch().h).abs() < 5.0);
+    }
+
+    #[test]
+    fn ensure_contrast_unreachable_returns_best() {
+        let gray: Rgb = "#777777".parse().unwrap();
+        let adjusted = gray.ensure_contrast(gray, 21.0);
+        assert!(adjusted == Rgb { r: 0, g: 0, b: 0 } || adjusted.r == 255);
+    }
 }
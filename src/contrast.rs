@@ -0,0 +1,158 @@
+use crate::{Error, Palette, Rgb, Rgba};
+use serde::Serialize;
+
+/// Minimum WCAG 2.x contrast ratio for normal text (level AA).
+pub const WCAG_AA: f64 = 4.5;
+
+/// Minimum WCAG 2.x contrast ratio for normal text (level AAA).
+pub const WCAG_AAA: f64 = 7.0;
+
+/// Foreground/background pairs checked by [`Palette::contrast_report`],
+/// besides every `semantic.*` color on `base.background`.
+const PAIRS: &[(&str, &str)] = &[
+    ("base.foreground", "base.background"),
+    ("state.selection_fg", "state.selection_bg"),
+    ("state.cursor_text", "state.cursor"),
+    ("state.diff_added", "state.diff_added_bg"),
+    ("state.diff_removed", "state.diff_removed_bg"),
+];
+
+/// Contrast of one foreground color on one background color.
+#[derive(Debug, Clone, Serialize)]
+pub struct ContrastCheck {
+    /// Key path of the foreground (e.g. "semantic.keyword")
+    pub foreground: String,
+    /// Key path of the background (e.g. "base.background")
+    pub background: String,
+    /// Resolved foreground color
+    pub foreground_color: Rgba,
+    /// Resolved background color
+    pub background_color: Rgba,
+    /// WCAG 2.x contrast ratio (1.0 to 21.0)
+    pub ratio: f64,
+    /// APCA lightness contrast (Lc), signed by polarity
+    pub apca: f64,
+}
+
+impl ContrastCheck {
+    /// Compute the contrast of `foreground` on `background`.
+    ///
+    /// A translucent foreground is composited over the background first;
+    /// the background's own alpha is ignored.
+    #[must_use]
+    pub fn new(
+        foreground: impl Into<String>,
+        foreground_color: Rgba,
+        background: impl Into<String>,
+        background_color: Rgba,
+    ) -> Self {
+        let bg: Rgb = background_color.rgb();
+        let fg: Rgb = foreground_color.over(bg);
+
+        Self {
+            foreground: foreground.into(),
+            background: background.into(),
+            foreground_color,
+            background_color,
+            ratio: fg.contrast_ratio(bg),
+            apca: fg.apca_contrast(bg),
+        }
+    }
+
+    /// Whether the WCAG contrast ratio reaches `min_ratio`.
+    #[must_use]
+    pub fn meets(&self, min_ratio: f64) -> bool {
+        self.ratio >= min_ratio
+    }
+}
+
+impl Palette {
+    /// Evaluate contrast for well-known foreground/background pairs.
+    ///
+    /// Covers every `semantic.*` color on `base.background`, selection,
+    /// cursor and diff colors on their respective backgrounds.
+    pub fn contrast_report(&self) -> Result<Vec<ContrastCheck>, Error> {
+        let semantic = (&self.semantic)
+            .into_iter()
+            .map(|(key, _)| (format!("semantic.{key}"), "base.background".to_string()));
+        let pairs = PAIRS
+            .iter()
+            .map(|&(fg, bg)| (fg.to_string(), bg.to_string()));
+
+        semantic
+            .chain(pairs)
+            .map(|(fg, bg)| {
+                let fg_color = self
+                    .get(&fg)
+                    .ok_or_else(|| Error::UnresolvedRef(fg.clone()))?;
+                let bg_color = self
+                    .get(&bg)
+                    .ok_or_else(|| Error::UnresolvedRef(bg.clone()))?;
+                Ok(ContrastCheck::new(&fg, fg_color, &bg, bg_color))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rgba(hex: &str) -> Rgba {
+        hex.parse().unwrap()
+    }
+
+    #[test]
+    fn check_black_on_white() {
+        let check = ContrastCheck::new("fg", rgba("#000000"), "bg", rgba("#FFFFFF"));
+        assert!((check.ratio - 21.0).abs() < 0.001);
+        assert!(check.apca > 100.0);
+        assert!(check.meets(WCAG_AAA));
+    }
+
+    #[test]
+    fn check_composites_translucent_foreground() {
+        let opaque = ContrastCheck::new("fg", rgba("#FFFFFF"), "bg", rgba("#000000"));
+        let translucent = ContrastCheck::new("fg", rgba("#FFFFFF80"), "bg", rgba("#000000"));
+        assert!(translucent.ratio < opaque.ratio);
+    }
+
+    #[test]
+    fn report_covers_semantic_and_state_pairs() {
+        let report = Palette::night().contrast_report().unwrap();
+
+        assert!(
+            report
+                .iter()
+                .any(|c| c.foreground == "semantic.keyword" && c.background == "base.background")
+        );
+        assert!(
+            report
+                .iter()
+                .any(|c| c.foreground == "state.selection_fg"
+                    && c.background == "state.selection_bg")
+        );
+        assert!(
+            report
+                .iter()
+                .any(|c| c.foreground == "state.cursor_text" && c.background == "state.cursor")
+        );
+        assert!(
+            report.iter().any(
+                |c| c.foreground == "state.diff_added" && c.background == "state.diff_added_bg"
+            )
+        );
+    }
+
+    #[test]
+    fn report_text_is_readable() {
+        for palette in [Palette::night(), Palette::dawn()] {
+            let report = palette.contrast_report().unwrap();
+            let text = report
+                .iter()
+                .find(|c| c.foreground == "base.foreground")
+                .unwrap();
+            assert!(text.meets(WCAG_AA), "{}: {}", palette.name, text.ratio);
+        }
+    }
+}
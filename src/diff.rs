@@ -0,0 +1,123 @@
+use crate::{Palette, Rgba};
+use serde::Serialize;
+
+/// A resolved color that differs between two palettes.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ColorChange {
+    /// Key path (e.g. "semantic.comment")
+    pub key: String,
+    /// Color in the old palette, `None` if the key was added
+    pub old: Option<Rgba>,
+    /// Color in the new palette, `None` if the key was removed
+    pub new: Option<Rgba>,
+    /// Perceptual distance ([`Rgb::delta_e`](crate::Rgb::delta_e)) when both
+    /// exist; ignores alpha
+    pub delta_e: Option<f64>,
+    /// Change in opacity (new minus old, 0.0-1.0 scale) when both exist
+    pub delta_alpha: Option<f64>,
+}
+
+impl Palette {
+    /// Every key whose resolved color differs from `other`, in palette order.
+    ///
+    /// Keys present in only one palette (extra keys) are reported as added or
+    /// removed. `self` is the old palette and `other` the new one.
+    #[must_use]
+    pub fn diff(&self, other: &Self) -> Vec<ColorChange> {
+        let mut changes: Vec<ColorChange> = self
+            .iter()
+            .filter_map(|(key, old)| {
+                let new = other.get(&key);
+                (new != Some(old)).then(|| ColorChange {
+                    delta_e: new.map(|new| old.rgb().delta_e(new.rgb())),
+                    delta_alpha: new.map(|new| new.alpha() - old.alpha()),
+                    old: Some(old),
+                    new,
+                    key,
+                })
+            })
+            .collect();
+
+        changes.extend(other.iter().filter(|(key, _)| self.get(key).is_none()).map(
+            |(key, new)| ColorChange {
+                key,
+                old: None,
+                new: Some(new),
+                delta_e: None,
+                delta_alpha: None,
+            },
+        ));
+        changes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Variant;
+
+    #[test]
+    fn identical_palettes_have_no_changes() {
+        assert!(Palette::night().diff(&Palette::night()).is_empty());
+    }
+
+    #[test]
+    fn pigment_change_propagates_to_derived_keys() {
+        let old = Palette::night();
+        let new = Palette::from_str(
+            "extends = \"akari-night\"\n\n[colors]\nnight = \"#8A9FB2\"\n",
+            Variant::NIGHT,
+        )
+        .unwrap();
+
+        let changes = old.diff(&new);
+        let keys: Vec<_> = changes.iter().map(|c| c.key.as_str()).collect();
+        assert!(keys.contains(&"colors.night"));
+        assert!(keys.contains(&"semantic.comment"));
+        assert!(keys.contains(&"state.hint"));
+        assert!(!keys.contains(&"base.background"));
+
+        let night = &changes[keys.iter().position(|k| *k == "colors.night").unwrap()];
+        assert_eq!(night.new, "#8A9FB2".parse().ok());
+        assert!(night.delta_e.unwrap() > 0.0);
+    }
+
+    #[test]
+    fn extra_keys_are_added_or_removed() {
+        let old = Palette::night();
+        let new = Palette::from_str(
+            "extends = \"akari-night\"\n\n[semantic]\noperator = \"semantic.keyword\"\n",
+            Variant::NIGHT,
+        )
+        .unwrap();
+
+        let added = old.diff(&new);
+        assert_eq!(added.len(), 1);
+        assert_eq!(added[0].key, "semantic.operator");
+        assert_eq!(added[0].old, None);
+
+        let removed = new.diff(&old);
+        assert_eq!(removed[0].new, None);
+        assert_eq!(removed[0].delta_e, None);
+    }
+
+    #[test]
+    fn alpha_only_change_is_reported() {
+        let opaque = Palette::from_str(
+            "extends = \"akari-night\"\n\n[state]\nselection_bg = \"base.background\"\n",
+            Variant::NIGHT,
+        )
+        .unwrap();
+        let translucent = Palette::from_str(
+            "extends = \"akari-night\"\n\n[state]\nselection_bg = \"alpha(base.background, 0.5)\"\n",
+            Variant::NIGHT,
+        )
+        .unwrap();
+
+        let changes = opaque.diff(&translucent);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].delta_e, Some(0.0));
+        let delta_alpha = changes[0].delta_alpha.unwrap();
+        assert!((delta_alpha + 0.5).abs() < 0.01, "{delta_alpha}");
+    }
+}
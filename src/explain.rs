@@ -0,0 +1,241 @@
+use crate::expr::ColorExpr;
+use crate::palette::eval_expr;
+use crate::{Palette, Rgba};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// How a resolved color was computed from the palette file.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Derivation {
+    /// A hex literal
+    Literal(Rgba),
+    /// A reference to another key, with that key's own derivation
+    Ref {
+        key: String,
+        derivation: Box<Derivation>,
+    },
+    /// A function applied to color arguments and a numeric amount
+    Call {
+        function: &'static str,
+        args: Vec<Derivation>,
+        amount: f64,
+        value: Rgba,
+    },
+}
+
+impl Derivation {
+    /// The color this step evaluates to.
+    #[must_use]
+    pub fn value(&self) -> Rgba {
+        match self {
+            Self::Literal(color) => *color,
+            Self::Ref { derivation, .. } => derivation.value(),
+            Self::Call { value, .. } => *value,
+        }
+    }
+
+    /// References used directly by this step, in argument order.
+    ///
+    /// Does not descend into the referenced keys' own derivations.
+    #[must_use]
+    pub fn refs(&self) -> Vec<(&str, &Self)> {
+        match self {
+            Self::Literal(_) => Vec::new(),
+            Self::Ref { key, derivation } => vec![(key.as_str(), &**derivation)],
+            Self::Call { args, .. } => args.iter().flat_map(Self::refs).collect(),
+        }
+    }
+}
+
+/// Single-line form with every reference annotated by its value,
+/// e.g. `lighten(colors.night → #7A8FA2, 0.25)`.
+impl fmt::Display for Derivation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Literal(color) => write!(f, "{color}"),
+            Self::Ref { key, derivation } => write!(f, "{key} → {}", derivation.value()),
+            Self::Call {
+                function,
+                args,
+                amount,
+                ..
+            } => {
+                write!(f, "{function}(")?;
+                for arg in args {
+                    write!(f, "{arg}, ")?;
+                }
+                write!(f, "{amount})")
+            }
+        }
+    }
+}
+
+/// Derivation of one palette key, displayed as a tree that follows each
+/// reference back to a literal:
+///
+/// ```text
+/// state.hint = semantic.comment → #9BABB9
+///   semantic.comment = lighten(colors.night → #7A8FA2, 0.25) = #9BABB9
+///     colors.night = #7A8FA2
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Explanation {
+    /// Key path that was explained (e.g. "semantic.comment")
+    pub key: String,
+    pub derivation: Derivation,
+}
+
+impl Explanation {
+    fn fmt_tree(
+        f: &mut fmt::Formatter<'_>,
+        key: &str,
+        derivation: &Derivation,
+        depth: usize,
+    ) -> fmt::Result {
+        let indent = "  ".repeat(depth);
+        match derivation {
+            Derivation::Literal(color) => writeln!(f, "{indent}{key} = {color}")?,
+            Derivation::Ref { .. } => writeln!(f, "{indent}{key} = {derivation}")?,
+            Derivation::Call { value, .. } => {
+                writeln!(f, "{indent}{key} = {derivation} = {value}")?;
+            }
+        }
+
+        let mut seen = Vec::new();
+        for (ref_key, ref_derivation) in derivation.refs() {
+            if !seen.contains(&ref_key) {
+                seen.push(ref_key);
+                Self::fmt_tree(f, ref_key, ref_derivation, depth + 1)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Self::fmt_tree(f, &self.key, &self.derivation, 0)
+    }
+}
+
+impl Palette {
+    /// Explain how the color at `key` (e.g. `"semantic.comment"`) was derived.
+    ///
+    /// Returns `None` if the palette has no such key.
+    #[must_use]
+    pub fn explain(&self, key: &str) -> Option<Explanation> {
+        let resolved: BTreeMap<String, Rgba> = self.iter().collect();
+        let derivation = derive(self.exprs.get(key)?, &self.exprs, &resolved)?;
+
+        Some(Explanation {
+            key: key.to_string(),
+            derivation,
+        })
+    }
+}
+
+/// Build the derivation of `expr`, following references through `exprs`.
+fn derive(
+    expr: &ColorExpr,
+    exprs: &BTreeMap<String, ColorExpr>,
+    resolved: &BTreeMap<String, Rgba>,
+) -> Option<Derivation> {
+    if let Some((function, args, amount)) = expr.call() {
+        return Some(Derivation::Call {
+            function,
+            args: args
+                .into_iter()
+                .map(|arg| derive(arg, exprs, resolved))
+                .collect::<Option<_>>()?,
+            amount,
+            value: eval_expr(resolved, expr).ok()?,
+        });
+    }
+
+    match expr {
+        ColorExpr::Ref { .. } => {
+            let key = expr.refs().pop()?;
+            let derivation = derive(exprs.get(&key)?, exprs, resolved)?;
+            Some(Derivation::Ref {
+                key,
+                derivation: Box::new(derivation),
+            })
+        }
+        _ => eval_expr(resolved, expr).ok().map(Derivation::Literal),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Variant;
+
+    #[test]
+    fn explain_function_of_reference() {
+        let palette = Palette::night();
+        let explanation = palette.explain("semantic.comment").unwrap();
+
+        match &explanation.derivation {
+            Derivation::Call {
+                function,
+                args,
+                amount,
+                value,
+            } => {
+                assert_eq!(*function, "lighten");
+                assert!((amount - 0.25).abs() < f64::EPSILON);
+                assert_eq!(*value, palette.semantic.comment);
+                assert_eq!(args.len(), 1);
+            }
+            other => panic!("expected Call, got {other:?}"),
+        }
+        assert_eq!(
+            explanation.derivation.to_string(),
+            "lighten(colors.night → #7A8FA2, 0.25)"
+        );
+    }
+
+    #[test]
+    fn explain_follows_references_to_literals() {
+        let palette = Palette::night();
+        let explanation = palette.explain("state.hint").unwrap();
+        let comment = &palette.semantic.comment;
+
+        assert_eq!(
+            explanation.to_string(),
+            format!(
+                "state.hint = semantic.comment → {comment}\n  \
+                 semantic.comment = lighten(colors.night → #7A8FA2, 0.25) = {comment}\n    \
+                 colors.night = #7A8FA2\n"
+            )
+        );
+    }
+
+    #[test]
+    fn explain_binary_function() {
+        let toml = Palette::NIGHT_TOML.replace(
+            r#"surface = "mix(base.background, colors.night, 0.15)""#,
+            r#"surface = "mix(base.background, base.background, 0.5)""#,
+        );
+        let palette = Palette::from_str(&toml, Variant::NIGHT).unwrap();
+        let explanation = palette.explain("layers.surface").unwrap();
+
+        // Repeated references are listed once
+        let lines: Vec<_> = explanation.to_string().lines().map(String::from).collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(explanation.derivation.refs().len(), 2);
+    }
+
+    #[test]
+    fn explain_literal_and_unknown_key() {
+        let palette = Palette::night();
+        let explanation = palette.explain("colors.night").unwrap();
+        assert_eq!(
+            explanation.derivation,
+            Derivation::Literal("#7A8FA2".parse().unwrap())
+        );
+        assert_eq!(explanation.to_string(), "colors.night = #7A8FA2\n");
+
+        assert!(palette.explain("semantic.nope").is_none());
+    }
+}
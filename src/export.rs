@@ -0,0 +1,246 @@
+use crate::{Error, Palette, Rgba};
+use serde::Serialize;
+use serde::ser::{SerializeMap, Serializer};
+use std::fmt::Write;
+use std::str::FromStr;
+
+/// Output format of [`Palette::export`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// JSON object keyed by path
+    Json,
+    /// TOML with quoted dotted keys (`"semantic.keyword" = "#E26A3B"`)
+    Toml,
+    /// YAML mapping keyed by path
+    Yaml,
+    /// Shell/dotenv assignments (`AKARI_SEMANTIC_KEYWORD="#E26A3B"`)
+    Env,
+}
+
+impl FromStr for ExportFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Self::Json),
+            "toml" => Ok(Self::Toml),
+            "yaml" => Ok(Self::Yaml),
+            "env" => Ok(Self::Env),
+            _ => Err(Error::UnknownFormat(s.to_string())),
+        }
+    }
+}
+
+/// One exported color with the expression it was resolved from.
+struct Entry {
+    path: String,
+    value: Rgba,
+    expr: Option<String>,
+}
+
+/// Serialized form of an entry: a bare hex string, or `{ value, expr }`.
+#[derive(Serialize)]
+#[serde(untagged)]
+enum EntryValue<'a> {
+    Plain(Rgba),
+    WithExpr { value: Rgba, expr: &'a str },
+}
+
+/// Entries as a map keyed by path, in palette order.
+struct Entries<'a>(&'a [Entry]);
+
+impl Serialize for Entries<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for entry in self.0 {
+            let value = match &entry.expr {
+                Some(expr) => EntryValue::WithExpr {
+                    value: entry.value,
+                    expr,
+                },
+                None => EntryValue::Plain(entry.value),
+            };
+            map.serialize_entry(&entry.path, &value)?;
+        }
+        map.end()
+    }
+}
+
+impl Palette {
+    /// Serialize every resolved color as a flat `path -> hex` mapping.
+    ///
+    /// With `expressions`, each value is paired with the source expression it
+    /// was resolved from (`{ value, expr }`; a comment line for `env`).
+    #[must_use]
+    pub fn export(&self, format: ExportFormat, expressions: bool) -> String {
+        let entries: Vec<Entry> = self
+            .iter()
+            .map(|(path, value)| Entry {
+                expr: expressions
+                    .then(|| self.exprs.get(&path).map(ToString::to_string))
+                    .flatten(),
+                path,
+                value,
+            })
+            .collect();
+
+        match format {
+            ExportFormat::Json => {
+                serde_json::to_string_pretty(&Entries(&entries)).expect("string map serializes")
+                    + "\n"
+            }
+            ExportFormat::Toml => {
+                toml::to_string(&Entries(&entries)).expect("string map serializes")
+            }
+            ExportFormat::Yaml => to_yaml(&entries),
+            ExportFormat::Env => to_env(&entries),
+        }
+    }
+}
+
+/// Block-style YAML. Keys and values are JSON strings, which are valid YAML
+/// double-quoted scalars, so escaping is left to `serde_json`.
+fn to_yaml(entries: &[Entry]) -> String {
+    let quote = |s: &str| serde_json::to_string(s).expect("string serializes");
+
+    let mut out = String::new();
+    for entry in entries {
+        let key = quote(&entry.path);
+        let value = quote(&entry.value.to_string());
+        let _ = match &entry.expr {
+            Some(expr) => writeln!(out, "{key}:\n  value: {value}\n  expr: {}", quote(expr)),
+            None => writeln!(out, "{key}: {value}"),
+        };
+    }
+    out
+}
+
+fn to_env(entries: &[Entry]) -> String {
+    let mut out = String::new();
+    for entry in entries {
+        // Literal colors would only repeat the value
+        if let Some(expr) = &entry.expr
+            && !expr.eq_ignore_ascii_case(&entry.value.to_string())
+        {
+            let _ = writeln!(out, "# {expr}");
+        }
+        let _ = writeln!(out, "{}=\"{}\"", env_name(&entry.path), entry.value);
+    }
+    out
+}
+
+/// Environment variable name for a path (`ansi.bright.red` -> `AKARI_ANSI_BRIGHT_RED`).
+fn env_name(path: &str) -> String {
+    let name: String = path
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("AKARI_{name}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_format() {
+        assert_eq!("json".parse::<ExportFormat>().unwrap(), ExportFormat::Json);
+        assert_eq!("env".parse::<ExportFormat>().unwrap(), ExportFormat::Env);
+        assert!(matches!(
+            "xml".parse::<ExportFormat>(),
+            Err(Error::UnknownFormat(name)) if name == "xml"
+        ));
+    }
+
+    #[test]
+    fn export_json() {
+        let palette = Palette::night();
+        let json: serde_json::Value =
+            serde_json::from_str(&palette.export(ExportFormat::Json, false)).unwrap();
+
+        let object = json.as_object().unwrap();
+        assert_eq!(object.len(), palette.iter().count());
+        assert_eq!(
+            object["semantic.comment"],
+            palette.semantic.comment.to_string()
+        );
+        assert_eq!(
+            object["ansi.bright.red"],
+            palette.ansi_bright.red.to_string()
+        );
+    }
+
+    #[test]
+    fn export_json_with_expressions() {
+        let palette = Palette::night();
+        let json: serde_json::Value =
+            serde_json::from_str(&palette.export(ExportFormat::Json, true)).unwrap();
+
+        let comment = &json["semantic.comment"];
+        assert_eq!(comment["value"], palette.semantic.comment.to_string());
+        assert_eq!(comment["expr"], "lighten(colors.night, 0.25)");
+    }
+
+    #[test]
+    fn export_toml() {
+        let palette = Palette::night();
+        let table: toml::Table = palette.export(ExportFormat::Toml, true).parse().unwrap();
+
+        assert_eq!(table.len(), palette.iter().count());
+        let comment = &table["semantic.comment"];
+        assert_eq!(
+            comment["value"].as_str(),
+            Some(palette.semantic.comment.to_string().as_str())
+        );
+        assert_eq!(
+            comment["expr"].as_str(),
+            Some("lighten(colors.night, 0.25)")
+        );
+    }
+
+    #[test]
+    fn export_yaml() {
+        let palette = Palette::night();
+        let yaml = palette.export(ExportFormat::Yaml, false);
+        assert!(yaml.starts_with(&format!(
+            "\"colors.lantern.ember\": \"{}\"\n",
+            palette.colors.lantern.ember
+        )));
+
+        let yaml = palette.export(ExportFormat::Yaml, true);
+        assert!(yaml.contains("\"semantic.comment\":\n  value: \"#"));
+        assert!(yaml.contains("  expr: \"lighten(colors.night, 0.25)\"\n"));
+    }
+
+    #[test]
+    fn export_env() {
+        let palette = Palette::night();
+        let env = palette.export(ExportFormat::Env, true);
+        assert!(env.starts_with("AKARI_COLORS_LANTERN_EMBER=\"#"));
+        assert!(env.contains(&format!(
+            "# brighten(ansi.red, 0.12)\nAKARI_ANSI_BRIGHT_RED=\"{}\"\n",
+            palette.ansi_bright.red
+        )));
+    }
+
+    #[test]
+    fn env_names() {
+        assert_eq!(env_name("state.diff-text"), "AKARI_STATE_DIFF_TEXT");
+        assert_eq!(env_name("ansi.bright.red"), "AKARI_ANSI_BRIGHT_RED");
+    }
+
+    #[test]
+    fn export_keeps_palette_order() {
+        let palette = Palette::night();
+        let json = palette.export(ExportFormat::Json, false);
+        assert!(json.starts_with("{\n  \"colors.lantern.ember\": \"#"));
+        let toml = palette.export(ExportFormat::Toml, false);
+        assert!(toml.starts_with("\"colors.lantern.ember\" = \"#"));
+    }
+}
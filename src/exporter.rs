@@ -0,0 +1,152 @@
+use crate::{Artifact, Error, Palette, Rgba};
+
+/// A theme format produced by Rust code rather than Tera templates.
+///
+/// Exporters registered with [`Generator::register`](crate::Generator::register)
+/// are listed by `available_tools` and run for `--tool <name>` and `--tool all`,
+/// alongside any files in `templates/<name>/` (e.g. a README).
+pub trait Exporter: Send + Sync {
+    /// Tool name, used for `--tool` and as the output directory.
+    fn name(&self) -> &str;
+
+    /// Build the artifacts for all palettes; paths are relative to the tool's
+    /// output directory (e.g. `Akari-Night.terminal`).
+    fn export(&self, palettes: &[Palette]) -> Result<Vec<Artifact>, Error>;
+}
+
+/// macOS Terminal.app profiles (`terminal/Akari-{Variant}.terminal`).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TerminalExporter;
+
+impl Exporter for TerminalExporter {
+    fn name(&self) -> &str {
+        "terminal"
+    }
+
+    fn export(&self, palettes: &[Palette]) -> Result<Vec<Artifact>, Error> {
+        palettes
+            .iter()
+            .map(|palette| {
+                let filename = format!("Akari-{}.terminal", palette.variant.title());
+                Ok(Artifact::text(
+                    filename,
+                    crate::terminal::generate(palette)?,
+                ))
+            })
+            .collect()
+    }
+}
+
+/// iTerm2 color presets (`iterm2/Akari-{Variant}.itermcolors`).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ItermExporter;
+
+impl Exporter for ItermExporter {
+    fn name(&self) -> &str {
+        "iterm2"
+    }
+
+    fn export(&self, palettes: &[Palette]) -> Result<Vec<Artifact>, Error> {
+        palettes
+            .iter()
+            .map(|palette| {
+                let filename = format!("Akari-{}.itermcolors", palette.variant.title());
+                Ok(Artifact::text(filename, crate::iterm::generate(palette)?))
+            })
+            .collect()
+    }
+}
+
+/// Xcode themes (`xcode/Akari-{Variant}.xccolortheme`).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct XcodeExporter;
+
+impl Exporter for XcodeExporter {
+    fn name(&self) -> &str {
+        "xcode"
+    }
+
+    fn export(&self, palettes: &[Palette]) -> Result<Vec<Artifact>, Error> {
+        palettes
+            .iter()
+            .map(|palette| {
+                let filename = format!("Akari-{}.xccolortheme", palette.variant.title());
+                Ok(Artifact::text(filename, crate::xcode::generate(palette)?))
+            })
+            .collect()
+    }
+}
+
+/// JetBrains IDE color schemes (`jetbrains/akari-{variant}.icls`).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JetBrainsExporter;
+
+impl Exporter for JetBrainsExporter {
+    fn name(&self) -> &str {
+        "jetbrains"
+    }
+
+    fn export(&self, palettes: &[Palette]) -> Result<Vec<Artifact>, Error> {
+        Ok(palettes
+            .iter()
+            .map(|palette| {
+                let filename = format!("akari-{}.icls", palette.variant.name());
+                Artifact::text(filename, crate::jetbrains::generate(palette))
+            })
+            .collect())
+    }
+}
+
+/// Adobe Swatch Exchange files (`ase/akari-{variant}.ase`).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AseExporter;
+
+impl Exporter for AseExporter {
+    fn name(&self) -> &str {
+        "ase"
+    }
+
+    fn export(&self, palettes: &[Palette]) -> Result<Vec<Artifact>, Error> {
+        Ok(palettes
+            .iter()
+            .map(|palette| {
+                let filename = format!("akari-{}.ase", palette.variant.name());
+                Artifact::binary(filename, crate::ase::generate(palette))
+            })
+            .collect())
+    }
+}
+
+/// GIMP/Inkscape palettes (`gpl/akari-{variant}.gpl`).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GplExporter;
+
+impl Exporter for GplExporter {
+    fn name(&self) -> &str {
+        "gpl"
+    }
+
+    fn export(&self, palettes: &[Palette]) -> Result<Vec<Artifact>, Error> {
+        Ok(palettes
+            .iter()
+            .map(|palette| {
+                let filename = format!("akari-{}.gpl", palette.variant.name());
+                Artifact::text(filename, crate::gpl::generate(palette))
+            })
+            .collect())
+    }
+}
+
+/// Palette colors grouped by section (`colors`, `base`, ..., `ansi`), each
+/// keyed by its path within the section (e.g. `lantern.ember`, `bright.red`).
+pub(crate) fn swatch_groups(palette: &Palette) -> Vec<(String, Vec<(String, Rgba)>)> {
+    let mut groups: Vec<(String, Vec<(String, Rgba)>)> = Vec::new();
+    for (path, color) in palette.iter() {
+        let (section, key) = path.split_once('.').unwrap_or(("", &path));
+        match groups.last_mut() {
+            Some((name, colors)) if name == section => colors.push((key.to_string(), color)),
+            _ => groups.push((section.to_string(), vec![(key.to_string(), color)])),
+        }
+    }
+    groups
+}
@@ -0,0 +1,880 @@
+//! Color expression language used by palette TOML values.
+//!
+//! Expressions are tokenized by [`Lexer`] and parsed by a recursive-descent
+//! [`Parser`], so function calls nest arbitrarily:
+//!
+//! ```text
+//! expr  = HEX | path | call
+//! path  = IDENT ("." IDENT)+
+//! call  = IDENT "(" arg ("," arg)* ")"
+//! arg   = expr | NUMBER
+//! ```
+
+use crate::{Error, Rgba};
+use std::fmt;
+use std::ops::Range;
+
+/// Palette sections that hold colors and can be referenced in color expressions.
+///
+/// `colors.lantern.*` and `ansi.bright.*` are nested tables; their keys are
+/// addressed as `"lantern.*"` and `"bright.*"` within the section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Section {
+    Colors,
+    Base,
+    Layers,
+    State,
+    Semantic,
+    Ansi,
+}
+
+impl Section {
+    /// All sections, in the order they appear in palette files.
+    pub(crate) const ALL: [Self; 6] = [
+        Self::Colors,
+        Self::Base,
+        Self::Layers,
+        Self::State,
+        Self::Semantic,
+        Self::Ansi,
+    ];
+
+    fn parse(s: &str) -> Result<Self, String> {
+        Self::ALL
+            .into_iter()
+            .find(|section| section.as_str() == s)
+            .ok_or_else(|| {
+                let names: Vec<_> = Self::ALL.iter().map(Self::as_str).collect();
+                format!(
+                    "unknown section '{s}' (expected one of: {})",
+                    names.join(", ")
+                )
+            })
+    }
+
+    pub(crate) const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Colors => "colors",
+            Self::Base => "base",
+            Self::Layers => "layers",
+            Self::State => "state",
+            Self::Semantic => "semantic",
+            Self::Ansi => "ansi",
+        }
+    }
+}
+
+/// A parsed color expression.
+///
+/// Supports:
+/// - Literal hex colors: `"#E26A3B"`, `"#E26A3B80"`
+/// - References: `"colors.lantern"`
+/// - Functions:
+///   - `"lighten(colors.lantern, 0.1)"` — increase lightness proportionally
+///   - `"darken(base.background, 0.2)"` — decrease lightness proportionally
+///   - `"brighten(ansi.red, 0.1)"` — adjust lightness by absolute amount
+///   - `"mix(base.background, colors.night, 0.15)"` — blend two colors
+///   - `"alpha(layers.border, 0.5)"` — set opacity (0.0 = transparent, 1.0 = opaque)
+///   - `"oklch_lighten(colors.night, 0.25)"` — increase perceived lightness (OKLCH)
+///   - `"oklch_darken(base.background, 0.2)"` — decrease perceived lightness (OKLCH)
+///   - `"oklch_mix(base.background, colors.night, 0.15)"` — blend in OKLCH
+///   - `"chroma(colors.lantern.mid, 0.8)"` — scale chroma, keeping lightness and hue
+///   - `"ensure_contrast(colors.lantern.far, base.background, 4.5)"` — adjust lightness
+///     minimally to reach a WCAG contrast ratio against the second color
+#[derive(Debug, Clone)]
+pub(crate) enum ColorExpr {
+    /// A literal hex color (e.g., "#E26A3B")
+    Literal(String),
+    /// A reference to another field (e.g., "colors.lantern")
+    Ref { section: Section, key: String },
+    /// Lighten a color by a factor (0.0 = unchanged, 1.0 = white)
+    Lighten(Box<ColorExpr>, f64),
+    /// Darken a color by a factor (0.0 = unchanged, 1.0 = black)
+    Darken(Box<ColorExpr>, f64),
+    /// Brighten a color by absolute amount (positive = brighter, negative = dimmer)
+    Brighten(Box<ColorExpr>, f64),
+    /// Mix two colors (0.0 = first color, 1.0 = second color)
+    Mix(Box<ColorExpr>, Box<ColorExpr>, f64),
+    /// Set the alpha channel (0.0 = transparent, 1.0 = opaque)
+    Alpha(Box<ColorExpr>, f64),
+    /// Lighten in OKLCH space (0.0 = unchanged, 1.0 = white)
+    OklchLighten(Box<ColorExpr>, f64),
+    /// Darken in OKLCH space (0.0 = unchanged, 1.0 = black)
+    OklchDarken(Box<ColorExpr>, f64),
+    /// Mix two colors in OKLCH space (0.0 = first color, 1.0 = second color)
+    OklchMix(Box<ColorExpr>, Box<ColorExpr>, f64),
+    /// Scale chroma in OKLCH space (0.0 = gray, 1.0 = unchanged)
+    Chroma(Box<ColorExpr>, f64),
+    /// Adjust the first color's lightness to reach a contrast ratio against the second
+    EnsureContrast(Box<ColorExpr>, Box<ColorExpr>, f64),
+}
+
+impl ColorExpr {
+    /// Key paths referenced anywhere in the expression (e.g. "colors.night").
+    pub(crate) fn refs(&self) -> Vec<String> {
+        match self {
+            Self::Literal(_) => Vec::new(),
+            Self::Ref { section, key } => vec![format!("{}.{key}", section.as_str())],
+            Self::Lighten(inner, _)
+            | Self::Darken(inner, _)
+            | Self::Brighten(inner, _)
+            | Self::Alpha(inner, _)
+            | Self::OklchLighten(inner, _)
+            | Self::OklchDarken(inner, _)
+            | Self::Chroma(inner, _) => inner.refs(),
+            Self::Mix(a, b, _) | Self::OklchMix(a, b, _) | Self::EnsureContrast(a, b, _) => {
+                let mut refs = a.refs();
+                refs.extend(b.refs());
+                refs
+            }
+        }
+    }
+
+    /// Function name, color arguments and numeric argument of a function call.
+    ///
+    /// Returns `None` for literals and references.
+    pub(crate) fn call(&self) -> Option<(&'static str, Vec<&Self>, f64)> {
+        let (name, args, amount) = match self {
+            Self::Literal(_) | Self::Ref { .. } => return None,
+            Self::Lighten(inner, n) => ("lighten", vec![&**inner], n),
+            Self::Darken(inner, n) => ("darken", vec![&**inner], n),
+            Self::Brighten(inner, n) => ("brighten", vec![&**inner], n),
+            Self::Alpha(inner, n) => ("alpha", vec![&**inner], n),
+            Self::OklchLighten(inner, n) => ("oklch_lighten", vec![&**inner], n),
+            Self::OklchDarken(inner, n) => ("oklch_darken", vec![&**inner], n),
+            Self::Chroma(inner, n) => ("chroma", vec![&**inner], n),
+            Self::Mix(a, b, n) => ("mix", vec![&**a, &**b], n),
+            Self::OklchMix(a, b, n) => ("oklch_mix", vec![&**a, &**b], n),
+            Self::EnsureContrast(a, b, n) => ("ensure_contrast", vec![&**a, &**b], n),
+        };
+        Some((name, args, *amount))
+    }
+}
+
+/// Canonical source form, e.g. `lighten(colors.night, 0.25)`.
+impl fmt::Display for ColorExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some((name, args, amount)) = self.call() {
+            write!(f, "{name}(")?;
+            for arg in args {
+                write!(f, "{arg}, ")?;
+            }
+            return write!(f, "{amount})");
+        }
+        match self {
+            Self::Literal(hex) => write!(f, "{hex}"),
+            Self::Ref { section, key } => write!(f, "{}.{key}", section.as_str()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A color expression that failed to parse, with the location of the problem.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExprError {
+    /// TOML key path of the expression (e.g. "semantic.comment"), if known
+    pub key: Option<String>,
+    /// The expression source text
+    pub expr: String,
+    /// Character range of the offending input within `expr`
+    pub span: Range<usize>,
+    pub message: String,
+}
+
+impl ExprError {
+    fn new(expr: &str, span: Range<usize>, message: impl Into<String>) -> Self {
+        Self {
+            key: None,
+            expr: expr.to_string(),
+            span,
+            message: message.into(),
+        }
+    }
+
+    /// Attach the TOML key path the expression was read from.
+    #[must_use]
+    pub fn with_key(mut self, key: impl Into<String>) -> Self {
+        self.key = Some(key.into());
+        self
+    }
+
+    /// 1-based column where the problem starts.
+    #[must_use]
+    pub const fn column(&self) -> usize {
+        self.span.start + 1
+    }
+}
+
+impl fmt::Display for ExprError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(key) = &self.key {
+            write!(f, "{key}: ")?;
+        }
+        let width = self.span.len().max(1);
+        writeln!(f, "{} (column {})", self.message, self.column())?;
+        writeln!(f, "    {}", self.expr)?;
+        write!(
+            f,
+            "    {}{}",
+            " ".repeat(self.span.start),
+            "^".repeat(width)
+        )
+    }
+}
+
+impl std::error::Error for ExprError {}
+
+impl From<ExprError> for Error {
+    fn from(e: ExprError) -> Self {
+        Self::InvalidColorExpr(Box::new(e))
+    }
+}
+
+/// Parse a color expression string into a ColorExpr.
+pub(crate) fn parse_color_expr(s: &str) -> Result<ColorExpr, ExprError> {
+    let tokens = Lexer::new(s).tokenize()?;
+    let mut parser = Parser {
+        src: s,
+        tokens,
+        pos: 0,
+    };
+    let expr = parser.expr()?;
+    parser.expect_end()?;
+    Ok(expr)
+}
+
+/// Whether `key` can be named in an expression: a letter or `_`, then
+/// letters, digits or `_`. Palette keys outside this grammar are unreachable.
+pub(crate) fn is_ident(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars.next().is_some_and(is_ident_start) && chars.all(is_ident_char)
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_'
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Ident(String),
+    Hex(String),
+    Number(f64),
+    Dot,
+    Comma,
+    LParen,
+    RParen,
+    End,
+}
+
+impl TokenKind {
+    fn describe(&self) -> String {
+        match self {
+            Self::Ident(name) => format!("'{name}'"),
+            Self::Hex(hex) => format!("'{hex}'"),
+            Self::Number(_) => "number".to_string(),
+            Self::Dot => "'.'".to_string(),
+            Self::Comma => "','".to_string(),
+            Self::LParen => "'('".to_string(),
+            Self::RParen => "')'".to_string(),
+            Self::End => "end of expression".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    /// Character range within the source
+    span: Range<usize>,
+}
+
+struct Lexer<'a> {
+    src: &'a str,
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(src: &'a str) -> Self {
+        Self {
+            src,
+            chars: src.chars().collect(),
+            pos: 0,
+        }
+    }
+
+    fn tokenize(mut self) -> Result<Vec<Token>, ExprError> {
+        let mut tokens = Vec::new();
+        loop {
+            let token = self.next_token()?;
+            let end = token.kind == TokenKind::End;
+            tokens.push(token);
+            if end {
+                return Ok(tokens);
+            }
+        }
+    }
+
+    fn peek(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+
+    fn take_while(&mut self, pred: impl Fn(char) -> bool) -> String {
+        let start = self.pos;
+        while self.peek(0).is_some_and(&pred) {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    fn next_token(&mut self) -> Result<Token, ExprError> {
+        self.take_while(char::is_whitespace);
+        let start = self.pos;
+
+        let Some(c) = self.peek(0) else {
+            return Ok(Token {
+                kind: TokenKind::End,
+                span: start..start,
+            });
+        };
+
+        let punct = match c {
+            '.' if !self.peek(1).is_some_and(|c| c.is_ascii_digit()) => Some(TokenKind::Dot),
+            ',' => Some(TokenKind::Comma),
+            '(' => Some(TokenKind::LParen),
+            ')' => Some(TokenKind::RParen),
+            _ => None,
+        };
+        if let Some(kind) = punct {
+            self.pos += 1;
+            return Ok(Token {
+                kind,
+                span: start..self.pos,
+            });
+        }
+
+        let kind = match c {
+            '#' => {
+                self.pos += 1;
+                let digits = self.take_while(|c| c.is_ascii_alphanumeric());
+                TokenKind::Hex(format!("#{digits}"))
+            }
+            c if is_ident_start(c) => TokenKind::Ident(self.take_while(is_ident_char)),
+            c if c.is_ascii_digit() || matches!(c, '.' | '-' | '+') => self.number()?,
+            c => {
+                return Err(ExprError::new(
+                    self.src,
+                    start..start + 1,
+                    format!("unexpected character '{c}'"),
+                ));
+            }
+        };
+
+        Ok(Token {
+            kind,
+            span: start..self.pos,
+        })
+    }
+
+    /// Lex numeric forms such as `1`, `0.25`, `.5`, `-0.1`, `+1e-2`.
+    fn number(&mut self) -> Result<TokenKind, ExprError> {
+        let start = self.pos;
+        let mut text = String::new();
+        if let Some(sign @ ('-' | '+')) = self.peek(0) {
+            text.push(sign);
+            self.pos += 1;
+        }
+        text += &self.take_while(|c| c.is_ascii_digit() || c == '.');
+        if let Some(e @ ('e' | 'E')) = self.peek(0) {
+            text.push(e);
+            self.pos += 1;
+            if let Some(sign @ ('-' | '+')) = self.peek(0) {
+                text.push(sign);
+                self.pos += 1;
+            }
+            text += &self.take_while(|c| c.is_ascii_digit());
+        }
+
+        text.parse().map(TokenKind::Number).map_err(|_| {
+            ExprError::new(
+                self.src,
+                start..self.pos.max(start + 1),
+                format!("invalid number '{text}'"),
+            )
+        })
+    }
+}
+
+/// A parsed function argument: either a color or a number.
+enum Arg {
+    Color(ColorExpr, Range<usize>),
+    Number(f64, Range<usize>),
+}
+
+struct Parser<'a> {
+    src: &'a str,
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos]
+    }
+
+    fn advance(&mut self) -> Token {
+        let token = self.tokens[self.pos].clone();
+        if token.kind != TokenKind::End {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn error(&self, span: Range<usize>, message: impl Into<String>) -> ExprError {
+        ExprError::new(self.src, span, message)
+    }
+
+    fn unexpected(&self, token: &Token, expected: &str) -> ExprError {
+        self.error(
+            token.span.clone(),
+            format!("expected {expected}, found {}", token.kind.describe()),
+        )
+    }
+
+    fn expect(&mut self, kind: &TokenKind) -> Result<Token, ExprError> {
+        let token = self.advance();
+        if &token.kind == kind {
+            Ok(token)
+        } else {
+            Err(self.unexpected(&token, &kind.describe()))
+        }
+    }
+
+    fn expect_end(&mut self) -> Result<(), ExprError> {
+        let token = self.advance();
+        match token.kind {
+            TokenKind::End => Ok(()),
+            _ => Err(self.unexpected(&token, "end of expression")),
+        }
+    }
+
+    fn expr(&mut self) -> Result<ColorExpr, ExprError> {
+        match self.arg()? {
+            Arg::Color(expr, _) => Ok(expr),
+            Arg::Number(_, span) => Err(self.error(span, "expected a color, found number")),
+        }
+    }
+
+    fn arg(&mut self) -> Result<Arg, ExprError> {
+        let token = self.advance();
+        match token.kind {
+            TokenKind::Number(n) => Ok(Arg::Number(n, token.span)),
+            TokenKind::Hex(hex) => match hex.parse::<Rgba>() {
+                Ok(_) => Ok(Arg::Color(ColorExpr::Literal(hex), token.span)),
+                Err(_) => Err(self.error(
+                    token.span,
+                    format!("invalid hex color '{hex}' (expected #RRGGBB or #RRGGBBAA)"),
+                )),
+            },
+            TokenKind::Ident(name) if self.peek().kind == TokenKind::LParen => {
+                self.call(name, token.span)
+            }
+            TokenKind::Ident(name) => self.path(name, token.span),
+            _ => Err(self.unexpected(&token, "a color")),
+        }
+    }
+
+    /// Parse `section.key[.key...]` after the section identifier.
+    fn path(&mut self, section: String, section_span: Range<usize>) -> Result<Arg, ExprError> {
+        let mut keys = Vec::new();
+        let mut end = section_span.end;
+        while self.peek().kind == TokenKind::Dot {
+            self.advance();
+            let token = self.advance();
+            match token.kind {
+                TokenKind::Ident(key) => {
+                    keys.push(key);
+                    end = token.span.end;
+                }
+                _ => return Err(self.unexpected(&token, "a key after '.'")),
+            }
+        }
+
+        if keys.is_empty() {
+            return Err(self.error(
+                section_span,
+                format!("expected a reference like '{section}.key' or a function call"),
+            ));
+        }
+        let section =
+            Section::parse(&section).map_err(|msg| self.error(section_span.clone(), msg))?;
+
+        Ok(Arg::Color(
+            ColorExpr::Ref {
+                section,
+                key: keys.join("."),
+            },
+            section_span.start..end,
+        ))
+    }
+
+    /// Parse `name(arg, ...)` after the function name.
+    fn call(&mut self, name: String, name_span: Range<usize>) -> Result<Arg, ExprError> {
+        self.expect(&TokenKind::LParen)?;
+        let mut args = vec![self.arg()?];
+        loop {
+            let token = self.advance();
+            match token.kind {
+                TokenKind::Comma => args.push(self.arg()?),
+                TokenKind::RParen => {
+                    let span = name_span.start..token.span.end;
+                    return self.build_call(&name, name_span, args, span);
+                }
+                _ => return Err(self.unexpected(&token, "',' or ')'")),
+            }
+        }
+    }
+
+    fn build_call(
+        &self,
+        name: &str,
+        name_span: Range<usize>,
+        args: Vec<Arg>,
+        span: Range<usize>,
+    ) -> Result<Arg, ExprError> {
+        let expr = match name {
+            "lighten" => self.unary(ColorExpr::Lighten, name, args, &span),
+            "darken" => self.unary(ColorExpr::Darken, name, args, &span),
+            "brighten" => self.unary(ColorExpr::Brighten, name, args, &span),
+            "alpha" => self.unary(ColorExpr::Alpha, name, args, &span),
+            "oklch_lighten" => self.unary(ColorExpr::OklchLighten, name, args, &span),
+            "oklch_darken" => self.unary(ColorExpr::OklchDarken, name, args, &span),
+            "chroma" => self.unary(ColorExpr::Chroma, name, args, &span),
+            "mix" => self.binary(ColorExpr::Mix, name, args, &span),
+            "oklch_mix" => self.binary(ColorExpr::OklchMix, name, args, &span),
+            "ensure_contrast" => self.binary(ColorExpr::EnsureContrast, name, args, &span),
+            _ => Err(self.error(name_span, format!("unknown function '{name}'"))),
+        }?;
+
+        Ok(Arg::Color(expr, span))
+    }
+
+    /// Build `name(color, number)`.
+    fn unary(
+        &self,
+        build: fn(Box<ColorExpr>, f64) -> ColorExpr,
+        name: &str,
+        args: Vec<Arg>,
+        span: &Range<usize>,
+    ) -> Result<ColorExpr, ExprError> {
+        match <[Arg; 2]>::try_from(args) {
+            Ok([color, n]) => Ok(build(Box::new(self.color_arg(color)?), self.number_arg(n)?)),
+            Err(args) => Err(self.arity(name, "color, number", args.len(), span)),
+        }
+    }
+
+    /// Build `name(color, color, number)`.
+    fn binary(
+        &self,
+        build: fn(Box<ColorExpr>, Box<ColorExpr>, f64) -> ColorExpr,
+        name: &str,
+        args: Vec<Arg>,
+        span: &Range<usize>,
+    ) -> Result<ColorExpr, ExprError> {
+        match <[Arg; 3]>::try_from(args) {
+            Ok([a, b, n]) => Ok(build(
+                Box::new(self.color_arg(a)?),
+                Box::new(self.color_arg(b)?),
+                self.number_arg(n)?,
+            )),
+            Err(args) => Err(self.arity(name, "color, color, number", args.len(), span)),
+        }
+    }
+
+    fn arity(&self, name: &str, signature: &str, found: usize, span: &Range<usize>) -> ExprError {
+        self.error(
+            span.clone(),
+            format!(
+                "{name}({signature}) takes {} arguments, found {found}",
+                signature.split(", ").count()
+            ),
+        )
+    }
+
+    fn color_arg(&self, arg: Arg) -> Result<ColorExpr, ExprError> {
+        match arg {
+            Arg::Color(expr, _) => Ok(expr),
+            Arg::Number(_, span) => Err(self.error(span, "expected a color, found number")),
+        }
+    }
+
+    fn number_arg(&self, arg: Arg) -> Result<f64, ExprError> {
+        match arg {
+            Arg::Number(n, _) => Ok(n),
+            Arg::Color(_, span) => Err(self.error(span, "expected a number, found color")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_ref(expr: &ColorExpr, expected_section: Section, expected_key: &str) -> bool {
+        matches!(expr, ColorExpr::Ref { section, key } if *section == expected_section && key == expected_key)
+    }
+
+    #[test]
+    fn parse_color_expr_literal() {
+        let expr = parse_color_expr("#E26A3B").unwrap();
+        assert!(matches!(expr, ColorExpr::Literal(s) if s == "#E26A3B"));
+    }
+
+    #[test]
+    fn parse_color_expr_reference() {
+        let expr = parse_color_expr("colors.lantern.mid").unwrap();
+        assert!(is_ref(&expr, Section::Colors, "lantern.mid"));
+    }
+
+    #[test]
+    fn parse_color_expr_lighten() {
+        let expr = parse_color_expr("lighten(colors.lantern.mid, 0.1)").unwrap();
+        match expr {
+            ColorExpr::Lighten(inner, factor) => {
+                assert!(is_ref(&inner, Section::Colors, "lantern.mid"));
+                assert!((factor - 0.1).abs() < 0.001);
+            }
+            _ => panic!("expected Lighten"),
+        }
+    }
+
+    #[test]
+    fn parse_color_expr_darken() {
+        let expr = parse_color_expr("darken(base.background, 0.2)").unwrap();
+        match expr {
+            ColorExpr::Darken(inner, factor) => {
+                assert!(is_ref(&inner, Section::Base, "background"));
+                assert!((factor - 0.2).abs() < 0.001);
+            }
+            _ => panic!("expected Darken"),
+        }
+    }
+
+    #[test]
+    fn parse_color_expr_nested() {
+        let expr = parse_color_expr("lighten(darken(colors.lantern.mid, 0.1), 0.2)").unwrap();
+        match expr {
+            ColorExpr::Lighten(inner, outer_factor) => {
+                assert!((outer_factor - 0.2).abs() < 0.001);
+                match *inner {
+                    ColorExpr::Darken(innermost, inner_factor) => {
+                        assert!(is_ref(&innermost, Section::Colors, "lantern.mid"));
+                        assert!((inner_factor - 0.1).abs() < 0.001);
+                    }
+                    _ => panic!("expected Darken"),
+                }
+            }
+            _ => panic!("expected Lighten"),
+        }
+    }
+
+    #[test]
+    fn parse_color_expr_mix() {
+        let expr = parse_color_expr("mix(base.background, colors.night, 0.15)").unwrap();
+        match expr {
+            ColorExpr::Mix(color1, color2, factor) => {
+                assert!(is_ref(&color1, Section::Base, "background"));
+                assert!(is_ref(&color2, Section::Colors, "night"));
+                assert!((factor - 0.15).abs() < 0.001);
+            }
+            _ => panic!("expected Mix"),
+        }
+    }
+
+    #[test]
+    fn parse_color_expr_nested_mix() {
+        let expr =
+            parse_color_expr("mix(mix(colors.life, colors.rain, 0.5), ansi.red, 0.2)").unwrap();
+        match expr {
+            ColorExpr::Mix(color1, color2, factor) => {
+                assert!(matches!(*color1, ColorExpr::Mix(_, _, f) if (f - 0.5).abs() < 0.001));
+                assert!(is_ref(&color2, Section::Ansi, "red"));
+                assert!((factor - 0.2).abs() < 0.001);
+            }
+            _ => panic!("expected Mix"),
+        }
+    }
+
+    #[test]
+    fn parse_color_expr_whitespace_and_numbers() {
+        let expr = parse_color_expr("  brighten ( ansi.red ,-.5 ) ").unwrap();
+        assert!(matches!(expr, ColorExpr::Brighten(_, f) if (f + 0.5).abs() < 0.001));
+
+        let expr = parse_color_expr("lighten(colors.night, 25e-2)").unwrap();
+        assert!(matches!(expr, ColorExpr::Lighten(_, f) if (f - 0.25).abs() < 0.001));
+
+        let expr = parse_color_expr("alpha(colors.night, 1)").unwrap();
+        assert!(matches!(expr, ColorExpr::Alpha(_, f) if (f - 1.0).abs() < 0.001));
+    }
+
+    #[test]
+    fn parse_color_expr_alpha() {
+        let expr = parse_color_expr("alpha(colors.night, 0.5)").unwrap();
+        match expr {
+            ColorExpr::Alpha(inner, alpha) => {
+                assert!(is_ref(&inner, Section::Colors, "night"));
+                assert!((alpha - 0.5).abs() < 0.001);
+            }
+            _ => panic!("expected Alpha"),
+        }
+    }
+
+    #[test]
+    fn parse_color_expr_oklch_functions() {
+        let expr = parse_color_expr("oklch_lighten(colors.night, 0.25)").unwrap();
+        assert!(matches!(expr, ColorExpr::OklchLighten(_, f) if (f - 0.25).abs() < 0.001));
+
+        let expr = parse_color_expr("oklch_darken(base.background, 0.2)").unwrap();
+        assert!(matches!(expr, ColorExpr::OklchDarken(_, f) if (f - 0.2).abs() < 0.001));
+
+        let expr = parse_color_expr("chroma(colors.lantern.mid, 0.8)").unwrap();
+        assert!(matches!(expr, ColorExpr::Chroma(_, f) if (f - 0.8).abs() < 0.001));
+
+        let expr = parse_color_expr("oklch_mix(base.background, colors.night, 0.15)").unwrap();
+        match expr {
+            ColorExpr::OklchMix(color1, color2, factor) => {
+                assert!(is_ref(&color1, Section::Base, "background"));
+                assert!(is_ref(&color2, Section::Colors, "night"));
+                assert!((factor - 0.15).abs() < 0.001);
+            }
+            _ => panic!("expected OklchMix"),
+        }
+    }
+
+    #[test]
+    fn parse_color_expr_references_any_section() {
+        let expr = parse_color_expr("layers.base").unwrap();
+        assert!(is_ref(&expr, Section::Layers, "base"));
+
+        let expr = parse_color_expr("state.cursor").unwrap();
+        assert!(is_ref(&expr, Section::State, "cursor"));
+
+        let expr = parse_color_expr("semantic.keyword").unwrap();
+        assert!(is_ref(&expr, Section::Semantic, "keyword"));
+    }
+
+    #[test]
+    fn parse_color_expr_rejects_unknown_sections() {
+        let err = parse_color_expr("mix(colours.night, base.background, 0.5)").unwrap_err();
+        assert!(err.message.contains("unknown section 'colours'"));
+        assert_eq!(err.span, 4..11);
+    }
+
+    #[test]
+    fn refs_collects_nested_references() {
+        let expr =
+            parse_color_expr("mix(lighten(colors.night, 0.1), semantic.comment, 0.5)").unwrap();
+        assert_eq!(expr.refs(), ["colors.night", "semantic.comment"]);
+        assert!(parse_color_expr("#E26A3B").unwrap().refs().is_empty());
+    }
+
+    #[test]
+    fn parse_color_expr_ansi_bright() {
+        // ansi.bright.* is parsed as Section::Ansi with key "bright.*"
+        let expr = parse_color_expr("ansi.bright.red").unwrap();
+        assert!(is_ref(&expr, Section::Ansi, "bright.red"));
+    }
+
+    #[test]
+    fn error_span_points_at_missing_comma() {
+        let err = parse_color_expr("lighten(colors.night 0.25)").unwrap_err();
+        assert_eq!(err.message, "expected ',' or ')', found number");
+        assert_eq!(err.span, 21..25);
+        assert_eq!(err.column(), 22);
+    }
+
+    #[test]
+    fn error_unknown_function() {
+        let err =
+            parse_color_expr("mix(lgihten(colors.night, 0.1), base.background, 0.5)").unwrap_err();
+        assert_eq!(err.message, "unknown function 'lgihten'");
+        assert_eq!(err.span, 4..11);
+    }
+
+    #[test]
+    fn error_wrong_arity() {
+        let err = parse_color_expr("mix(colors.night, 0.5)").unwrap_err();
+        assert!(err.message.contains("takes 3 arguments, found 2"));
+        assert_eq!(err.span, 0..22);
+    }
+
+    #[test]
+    fn error_argument_types() {
+        let err = parse_color_expr("lighten(0.5, colors.night)").unwrap_err();
+        assert_eq!(err.message, "expected a color, found number");
+        assert_eq!(err.span, 8..11);
+
+        let err = parse_color_expr("lighten(colors.night, base.background)").unwrap_err();
+        assert_eq!(err.message, "expected a number, found color");
+        assert_eq!(err.span, 22..37);
+    }
+
+    #[test]
+    fn error_invalid_hex() {
+        let err = parse_color_expr("mix(#12345, base.background, 0.5)").unwrap_err();
+        assert!(err.message.contains("invalid hex color '#12345'"));
+        assert_eq!(err.span, 4..10);
+    }
+
+    #[test]
+    fn error_trailing_input() {
+        let err = parse_color_expr("colors.night)").unwrap_err();
+        assert_eq!(err.message, "expected end of expression, found ')'");
+        assert_eq!(err.span, 12..13);
+    }
+
+    #[test]
+    fn error_bare_section() {
+        let err = parse_color_expr("colors").unwrap_err();
+        assert!(err.message.contains("expected a reference"));
+    }
+
+    #[test]
+    fn error_unclosed_call() {
+        let err = parse_color_expr("lighten(colors.night, 0.1").unwrap_err();
+        assert_eq!(err.message, "expected ',' or ')', found end of expression");
+        assert_eq!(err.span, 25..25);
+    }
+
+    #[test]
+    fn error_display_has_key_and_caret() {
+        let err = parse_color_expr("lighten(colors.night 0.25)")
+            .unwrap_err()
+            .with_key("semantic.comment");
+        assert_eq!(
+            err.to_string(),
+            "semantic.comment: expected ',' or ')', found number (column 22)\n    \
+             lighten(colors.night 0.25)\n    \
+             \u{20}                    ^^^^"
+        );
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for src in [
+            "#E26A3B80",
+            "ansi.bright.red",
+            "lighten(colors.night, 0.25)",
+            "mix(darken(base.background, 0.1), colors.lantern.mid, 0.5)",
+            "ensure_contrast(colors.lantern.far, base.background, 4.5)",
+        ] {
+            assert_eq!(parse_color_expr(src).unwrap().to_string(), src);
+        }
+        let expr = parse_color_expr("alpha( layers.border ,.5 )").unwrap();
+        assert_eq!(expr.to_string(), "alpha(layers.border, 0.5)");
+    }
+}
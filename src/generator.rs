@@ -1,6 +1,11 @@
-use crate::{Artifact, Error, Palette, Rgb, VARIANTS};
+use crate::output::ContentHasher;
+use crate::{
+    Artifact, AseExporter, Error, Exporter, GplExporter, ItermExporter, JetBrainsExporter, Palette,
+    Rgb, Rgba, TerminalExporter, XcodeExporter,
+};
 use std::collections::HashMap;
 use std::ffi::OsStr;
+use std::hash::{Hash, Hasher};
 use std::path::{Path, PathBuf};
 use tera::{Context, Tera, Value};
 use walkdir::WalkDir;
@@ -25,9 +30,24 @@ fn hex_to_rgb_space_filter(value: &Value, _args: &HashMap<String, Value>) -> ter
     Ok(Value::String(rgb.to_space_separated()))
 }
 
+fn alpha_filter(value: &Value, args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let hex = value
+        .as_str()
+        .ok_or_else(|| tera::Error::msg("alpha requires a string"))?;
+    let alpha = args
+        .get("value")
+        .and_then(Value::as_f64)
+        .ok_or_else(|| tera::Error::msg("alpha requires a numeric `value` argument"))?;
+    let rgba: Rgba = hex
+        .parse()
+        .map_err(|e: crate::Error| tera::Error::msg(e.to_string()))?;
+    Ok(Value::String(rgba.with_alpha(alpha).to_string()))
+}
+
 pub struct Generator {
     tera: Tera,
     templates_dir: PathBuf,
+    exporters: Vec<Box<dyn Exporter>>,
 }
 
 impl Generator {
@@ -43,47 +63,114 @@ impl Generator {
         })?;
         tera.register_filter("hex_to_rgb", hex_to_rgb_filter);
         tera.register_filter("hex_to_rgb_space", hex_to_rgb_space_filter);
-        Ok(Self {
+        tera.register_filter("alpha", alpha_filter);
+        let mut generator = Self {
             tera,
             templates_dir,
+            exporters: Vec::new(),
+        };
+        generator.register(TerminalExporter);
+        generator.register(ItermExporter);
+        generator.register(JetBrainsExporter);
+        generator.register(XcodeExporter);
+        generator.register(AseExporter);
+        generator.register(GplExporter);
+        Ok(generator)
+    }
+
+    /// Add a programmatic exporter, replacing any registered under the same name
+    pub fn register(&mut self, exporter: impl Exporter + 'static) {
+        self.exporters.retain(|e| e.name() != exporter.name());
+        self.exporters.push(Box::new(exporter));
+    }
+
+    /// Re-read every template from disk, picking up added and removed files
+    pub fn reload(&mut self) -> Result<(), Error> {
+        self.tera.full_reload().map_err(|e| Error::Template {
+            context: "reload failed",
+            source: e,
         })
     }
 
-    /// Generate artifacts for a specific tool
-    pub fn generate_tool(
-        &self,
-        tool: &str,
-        night: &Palette,
-        dawn: &Palette,
-    ) -> Result<Vec<Artifact>, Error> {
+    /// Generate artifacts for a specific tool, once per palette for `{name}` templates
+    pub fn generate_tool(&self, tool: &str, palettes: &[Palette]) -> Result<Vec<Artifact>, Error> {
         let mut artifacts = Vec::new();
 
-        // Terminal requires special handling (plist with NSColor binary encoding)
-        if tool == "terminal" {
-            self.generate_terminal(&mut artifacts, night, dawn)?;
+        if let Some(exporter) = self.exporters.iter().find(|e| e.name() == tool) {
+            for mut artifact in exporter.export(palettes)? {
+                artifact.rel_path = PathBuf::from(tool).join(artifact.rel_path);
+                artifacts.push(artifact);
+            }
         }
 
-        self.process_tool_directory(tool, &mut artifacts, night, dawn)?;
+        self.process_tool_directory(tool, &mut artifacts, palettes)?;
 
         Ok(artifacts)
     }
 
-    /// Generate Terminal.app theme files
-    fn generate_terminal(
+    /// Generate artifacts for several tools in parallel, in the order of `tools`
+    pub fn generate_tools(
         &self,
-        artifacts: &mut Vec<Artifact>,
-        night: &Palette,
-        dawn: &Palette,
-    ) -> Result<(), Error> {
-        for palette in [night, dawn] {
-            let content = crate::terminal::generate(palette)?;
-            let filename = format!("Akari-{}.terminal", palette.variant.title());
-            artifacts.push(Artifact::text(
-                PathBuf::from("terminal").join(filename),
-                content,
-            ));
+        tools: &[String],
+        palettes: &[Palette],
+    ) -> Result<Vec<Artifact>, Error> {
+        let results: Vec<_> = std::thread::scope(|scope| {
+            let handles: Vec<_> = tools
+                .iter()
+                .map(|tool| scope.spawn(|| self.generate_tool(tool, palettes)))
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().expect("generator thread panicked"))
+                .collect()
+        });
+
+        let mut artifacts = Vec::new();
+        for result in results {
+            artifacts.extend(result?);
         }
-        Ok(())
+        Ok(artifacts)
+    }
+
+    /// Hash of everything a tool's output depends on: the resolved palettes,
+    /// the files under `templates/<tool>`, the output of a registered exporter
+    /// and the generator's version and sources.
+    pub fn fingerprint(&self, tool: &str, palettes: &[Palette]) -> Result<u64, Error> {
+        let mut hasher = ContentHasher::default();
+        tool.hash(&mut hasher);
+
+        for palette in palettes {
+            serde_json::to_string(palette)
+                .map_err(std::io::Error::other)?
+                .hash(&mut hasher);
+        }
+
+        // Exporter-only tools have no template directory
+        let tool_dir = self.templates_dir.join(tool);
+        if tool_dir.is_dir() {
+            for entry in WalkDir::new(&tool_dir).sort_by_file_name() {
+                let entry = entry.map_err(std::io::Error::from)?;
+                if entry.file_type().is_file() {
+                    entry.path().strip_prefix(&tool_dir).ok().hash(&mut hasher);
+                    std::fs::read(entry.path())?.hash(&mut hasher);
+                }
+            }
+        }
+
+        // Exporters render in code, so their output is the input. Cheap next
+        // to templates, and covers exporters registered from outside the crate.
+        if let Some(exporter) = self.exporters.iter().find(|e| e.name() == tool) {
+            for artifact in exporter.export(palettes)? {
+                artifact.rel_path.hash(&mut hasher);
+                artifact.bytes()?.hash(&mut hasher);
+            }
+        }
+
+        // A rebuilt generator may render differently from the same inputs
+        env!("CARGO_PKG_VERSION").hash(&mut hasher);
+        env!("AKARI_SOURCE_DIGEST").hash(&mut hasher);
+
+        Ok(hasher.finish())
     }
 
     /// Walk tool directory and process files
@@ -91,8 +178,7 @@ impl Generator {
         &self,
         tool: &str,
         artifacts: &mut Vec<Artifact>,
-        night: &Palette,
-        dawn: &Palette,
+        palettes: &[Palette],
     ) -> Result<(), Error> {
         let tool_dir = self.templates_dir.join(tool);
 
@@ -107,7 +193,7 @@ impl Generator {
                 .map_err(|_| Error::InvalidPath(path.to_path_buf()))?;
 
             if path.extension() == Some(OsStr::new("tera")) {
-                self.process_template(tool, path, rel_path, artifacts, night, dawn)?;
+                self.process_template(tool, path, rel_path, artifacts, palettes)?;
             } else {
                 self.process_static(tool, path, rel_path, artifacts);
             }
@@ -123,8 +209,7 @@ impl Generator {
         path: &Path,
         rel_path: &Path,
         artifacts: &mut Vec<Artifact>,
-        night: &Palette,
-        dawn: &Palette,
+        palettes: &[Palette],
     ) -> Result<(), Error> {
         let out_path = strip_tera_extension(rel_path);
         let out_str = out_path.to_string_lossy();
@@ -136,34 +221,29 @@ impl Generator {
             .replace('\\', "/"); // Windows compatibility
 
         if out_str.contains("{name}") || out_str.contains("{Name}") {
-            self.render_per_variant(tool, &template_name, &out_str, artifacts, night, dawn)?;
+            self.render_per_variant(tool, &template_name, &out_str, artifacts, palettes)?;
         } else {
-            let content = self.render_combined(&template_name, night, dawn)?;
+            let content = self.render_combined(&template_name, palettes)?;
             artifacts.push(Artifact::text(PathBuf::from(tool).join(out_path), content));
         }
 
         Ok(())
     }
 
-    /// Render template for each variant (night/dawn)
+    /// Render template for each variant
     fn render_per_variant(
         &self,
         tool: &str,
         template_name: &str,
         out_pattern: &str,
         artifacts: &mut Vec<Artifact>,
-        night: &Palette,
-        dawn: &Palette,
+        palettes: &[Palette],
     ) -> Result<(), Error> {
-        for variant in VARIANTS {
-            let palette = match variant {
-                crate::Variant::Night => night,
-                crate::Variant::Dawn => dawn,
-            };
+        for palette in palettes {
             let content = self.render(template_name, palette)?;
             let final_path = out_pattern
-                .replace("{name}", variant.name())
-                .replace("{Name}", variant.title());
+                .replace("{name}", palette.variant.name())
+                .replace("{Name}", &palette.variant.title());
             artifacts.push(Artifact::text(
                 PathBuf::from(tool).join(&*final_path),
                 content,
@@ -186,9 +266,13 @@ impl Generator {
         ));
     }
 
-    /// List available tools (directories in templates/)
+    /// List available tools (directories in templates/ and registered exporters), sorted
     pub fn available_tools(&self) -> std::io::Result<Vec<String>> {
-        let mut tools = Vec::new();
+        let mut tools: Vec<String> = self
+            .exporters
+            .iter()
+            .map(|e| e.name().to_string())
+            .collect();
         for entry in std::fs::read_dir(&self.templates_dir)? {
             let entry = entry?;
             if entry.file_type()?.is_dir()
@@ -197,6 +281,8 @@ impl Generator {
                 tools.push(name);
             }
         }
+        tools.sort();
+        tools.dedup();
         Ok(tools)
     }
 
@@ -223,29 +309,24 @@ impl Generator {
             })
     }
 
-    fn render_combined(
-        &self,
-        template: &str,
-        night: &Palette,
-        dawn: &Palette,
-    ) -> Result<String, Error> {
+    /// Render a template that sees every palette at once.
+    ///
+    /// Palettes are available as a `variants` list and as `{variant}_*` keys
+    /// (e.g. `night_base`, with `-` in variant names replaced by `_`).
+    fn render_combined(&self, template: &str, palettes: &[Palette]) -> Result<String, Error> {
         let mut context = Context::new();
 
-        context.insert("night_colors", &night.colors);
-        context.insert("night_base", &night.base);
-        context.insert("night_layers", &night.layers);
-        context.insert("night_state", &night.state);
-        context.insert("night_semantic", &night.semantic);
-        context.insert("night_ansi", &night.ansi);
-        context.insert("night_ansi_bright", &night.ansi_bright);
-
-        context.insert("dawn_colors", &dawn.colors);
-        context.insert("dawn_base", &dawn.base);
-        context.insert("dawn_layers", &dawn.layers);
-        context.insert("dawn_state", &dawn.state);
-        context.insert("dawn_semantic", &dawn.semantic);
-        context.insert("dawn_ansi", &dawn.ansi);
-        context.insert("dawn_ansi_bright", &dawn.ansi_bright);
+        context.insert("variants", palettes);
+        for palette in palettes {
+            let prefix = palette.variant.name().replace('-', "_");
+            context.insert(format!("{prefix}_colors"), &palette.colors);
+            context.insert(format!("{prefix}_base"), &palette.base);
+            context.insert(format!("{prefix}_layers"), &palette.layers);
+            context.insert(format!("{prefix}_state"), &palette.state);
+            context.insert(format!("{prefix}_semantic"), &palette.semantic);
+            context.insert(format!("{prefix}_ansi"), &palette.ansi);
+            context.insert(format!("{prefix}_ansi_bright"), &palette.ansi_bright);
+        }
 
         self.tera
             .render(template, &context)
@@ -0,0 +1,52 @@
+//! GIMP palettes (`.gpl`), also read by Inkscape and Krita.
+
+use crate::Palette;
+use crate::exporter::swatch_groups;
+use std::fmt::Write;
+
+/// Generate a GIMP palette; sections become comment headers and each color is
+/// named by its full key path. Alpha is dropped.
+#[must_use]
+pub fn generate(palette: &Palette) -> String {
+    let mut out = String::from("GIMP Palette\n");
+    let _ = writeln!(out, "Name: Akari {}", palette.variant.title());
+    out.push_str("Columns: 8\n#\n");
+
+    for (section, colors) in swatch_groups(palette) {
+        let _ = writeln!(out, "# {section}");
+        for (key, color) in colors {
+            let rgb = color.rgb();
+            let _ = writeln!(out, "{:3} {:3} {:3}\t{section}.{key}", rgb.r, rgb.g, rgb.b);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generate_lists_every_color() {
+        let palette = Palette::night();
+        let gpl = generate(&palette);
+        assert!(gpl.starts_with("GIMP Palette\nName: Akari Night\nColumns: 8\n#\n# colors\n"));
+
+        let entries: Vec<&str> = gpl
+            .lines()
+            .filter(|line| !line.starts_with('#') && line.contains('\t'))
+            .collect();
+        assert_eq!(entries.len(), palette.iter().count());
+
+        let ember = palette.colors.lantern.ember.rgb();
+        assert_eq!(
+            entries[0],
+            format!(
+                "{:3} {:3} {:3}\tcolors.lantern.ember",
+                ember.r, ember.g, ember.b
+            )
+        );
+        assert!(gpl.contains("# ansi\n"));
+        assert!(gpl.contains("\tansi.bright.red\n"));
+    }
+}
@@ -0,0 +1,126 @@
+use crate::{Error, Palette, Rgba};
+use plist::Value;
+use std::collections::BTreeMap;
+
+/// iTerm2 color dictionary: sRGB float components in 0.0-1.0.
+fn color_dict(color: Rgba) -> Value {
+    let (r, g, b) = color.rgb().as_floats();
+    let dict = BTreeMap::from([
+        (
+            "Alpha Component".to_string(),
+            Value::Real(f64::from(color.a) / 255.0),
+        ),
+        ("Blue Component".to_string(), Value::Real(b)),
+        ("Color Space".to_string(), Value::String("sRGB".to_string())),
+        ("Green Component".to_string(), Value::Real(g)),
+        ("Red Component".to_string(), Value::Real(r)),
+    ]);
+    Value::Dictionary(dict.into_iter().collect())
+}
+
+/// Generate an iTerm2 color preset (`.itermcolors`) for a palette.
+pub fn generate(palette: &Palette) -> Result<String, Error> {
+    let mut dict: BTreeMap<String, Value> = BTreeMap::new();
+
+    // ANSI 0-7 then bright 8-15
+    let ansi = (&palette.ansi).into_iter().chain(&palette.ansi_bright);
+    for (index, (_, color)) in ansi.enumerate() {
+        dict.insert(format!("Ansi {index} Color"), color_dict(color));
+    }
+
+    // Base colors
+    dict.insert(
+        "Background Color".to_string(),
+        color_dict(palette.base.background),
+    );
+    dict.insert(
+        "Foreground Color".to_string(),
+        color_dict(palette.base.foreground),
+    );
+    dict.insert(
+        "Bold Color".to_string(),
+        color_dict(palette.base.foreground),
+    );
+
+    // Cursor
+    dict.insert("Cursor Color".to_string(), color_dict(palette.state.cursor));
+    dict.insert(
+        "Cursor Text Color".to_string(),
+        color_dict(palette.state.cursor_text),
+    );
+
+    // Selection
+    dict.insert(
+        "Selection Color".to_string(),
+        color_dict(palette.state.selection_bg),
+    );
+    dict.insert(
+        "Selected Text Color".to_string(),
+        color_dict(palette.state.selection_fg),
+    );
+
+    // Accents; the badge is drawn over text, so keep it translucent
+    dict.insert("Link Color".to_string(), color_dict(palette.semantic.link));
+    dict.insert(
+        "Badge Color".to_string(),
+        color_dict(palette.colors.lantern.mid.with_alpha(0.5)),
+    );
+
+    crate::terminal::to_xml(dict)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load(content: &str) -> plist::Dictionary {
+        Value::from_reader_xml(content.as_bytes())
+            .unwrap()
+            .into_dictionary()
+            .unwrap()
+    }
+
+    #[test]
+    fn generate_has_all_keys() {
+        let dict = load(&generate(&Palette::night()).unwrap());
+
+        for index in 0..16 {
+            assert!(dict.contains_key(&format!("Ansi {index} Color")));
+        }
+        for key in [
+            "Background Color",
+            "Foreground Color",
+            "Bold Color",
+            "Cursor Color",
+            "Cursor Text Color",
+            "Selection Color",
+            "Selected Text Color",
+            "Link Color",
+            "Badge Color",
+        ] {
+            assert!(dict.contains_key(key), "missing {key}");
+        }
+        assert_eq!(dict.len(), 25);
+    }
+
+    #[test]
+    fn color_components() {
+        let palette = Palette::night();
+        let dict = load(&generate(&palette).unwrap());
+
+        let background = dict["Background Color"].as_dictionary().unwrap();
+        let (r, g, b) = palette.base.background.rgb().as_floats();
+        assert_eq!(background["Red Component"].as_real(), Some(r));
+        assert_eq!(background["Green Component"].as_real(), Some(g));
+        assert_eq!(background["Blue Component"].as_real(), Some(b));
+        assert_eq!(background["Alpha Component"].as_real(), Some(1.0));
+        assert_eq!(background["Color Space"].as_string(), Some("sRGB"));
+
+        let bright_red = dict["Ansi 9 Color"].as_dictionary().unwrap();
+        let (r, ..) = palette.ansi_bright.red.rgb().as_floats();
+        assert_eq!(bright_red["Red Component"].as_real(), Some(r));
+
+        let badge = dict["Badge Color"].as_dictionary().unwrap();
+        assert!(badge["Alpha Component"].as_real().unwrap() < 1.0);
+    }
+}
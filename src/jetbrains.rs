@@ -0,0 +1,283 @@
+//! JetBrains IDE color schemes (`.icls`) for IntelliJ IDEA, RustRover, etc.
+
+use crate::xml::Element;
+use crate::{Palette, Rgb, Rgba};
+
+/// `FONT_TYPE` value for italic text.
+const ITALIC: &str = "2";
+/// `EFFECT_TYPE` values.
+const UNDERSCORE: &str = "1";
+const WAVE_UNDERSCORE: &str = "2";
+
+/// Text attributes of one JetBrains attribute key.
+#[derive(Default)]
+struct Style {
+    foreground: Option<Rgba>,
+    background: Option<Rgba>,
+    effect: Option<(Rgba, &'static str)>,
+    stripe: Option<Rgba>,
+    font_type: Option<&'static str>,
+}
+
+impl Style {
+    fn fg(color: Rgba) -> Self {
+        Self {
+            foreground: Some(color),
+            ..Self::default()
+        }
+    }
+
+    fn bg(color: Rgba) -> Self {
+        Self {
+            background: Some(color),
+            ..Self::default()
+        }
+    }
+
+    fn effect(color: Rgba, kind: &'static str) -> Self {
+        Self {
+            effect: Some((color, kind)),
+            stripe: Some(color),
+            ..Self::default()
+        }
+    }
+
+    fn italic(self) -> Self {
+        Self {
+            font_type: Some(ITALIC),
+            ..self
+        }
+    }
+
+    fn stripe(self, color: Rgba) -> Self {
+        Self {
+            stripe: Some(color),
+            ..self
+        }
+    }
+
+    fn element(&self, key: &'static str, background: Rgb) -> Element {
+        let mut value = Element::new("value");
+        let mut color = |name, color: Option<Rgba>| {
+            if let Some(color) = color {
+                value.push(option(name, hex(color, background)));
+            }
+        };
+        color("FOREGROUND", self.foreground);
+        color("BACKGROUND", self.background);
+        color("EFFECT_COLOR", self.effect.map(|(color, _)| color));
+        color("ERROR_STRIPE_COLOR", self.stripe);
+        if let Some((_, kind)) = self.effect {
+            value.push(option("EFFECT_TYPE", kind));
+        }
+        if let Some(font_type) = self.font_type {
+            value.push(option("FONT_TYPE", font_type));
+        }
+        Element::new("option").attr("name", key).child(value)
+    }
+}
+
+fn option(name: &'static str, value: impl Into<String>) -> Element {
+    Element::new("option")
+        .attr("name", name)
+        .attr("value", value)
+}
+
+/// `RRGGBB` as JetBrains expects; translucent colors are blended over `background`.
+fn hex(color: Rgba, background: Rgb) -> String {
+    let Rgb { r, g, b } = color.over(background);
+    format!("{r:02X}{g:02X}{b:02X}")
+}
+
+/// Generate a JetBrains color scheme for a palette.
+#[must_use]
+pub fn generate(palette: &Palette) -> String {
+    let background = palette.base.background.rgb();
+    let dark = background.relative_luminance() < 0.5;
+    let Palette {
+        base,
+        layers,
+        state,
+        semantic,
+        ansi,
+        ansi_bright,
+        ..
+    } = palette;
+
+    let mut colors = Element::new("colors");
+    for (key, color) in [
+        ("CARET_COLOR", state.cursor),
+        ("CARET_ROW_COLOR", layers.surface),
+        ("SELECTION_BACKGROUND", state.selection_bg),
+        ("SELECTION_FOREGROUND", state.selection_fg),
+        ("GUTTER_BACKGROUND", layers.base),
+        ("LINE_NUMBERS_COLOR", semantic.comment),
+        ("LINE_NUMBER_ON_CARET_ROW_COLOR", base.foreground),
+        ("INDENT_GUIDE", layers.border),
+        ("SELECTED_INDENT_GUIDE", semantic.comment),
+        ("RIGHT_MARGIN_COLOR", layers.border),
+        ("TEARLINE_COLOR", layers.border),
+        ("WHITESPACES", layers.border),
+        ("CONSOLE_BACKGROUND_KEY", base.background),
+        ("DOCUMENTATION_COLOR", layers.raised),
+        ("NOTIFICATION_BACKGROUND", layers.raised),
+        ("ADDED_LINES_COLOR", state.diff_added),
+        ("MODIFIED_LINES_COLOR", state.diff_changed),
+        ("DELETED_LINES_COLOR", state.diff_removed),
+        ("FILESTATUS_ADDED", state.diff_added),
+        ("FILESTATUS_MODIFIED", state.diff_changed),
+        ("FILESTATUS_DELETED", state.diff_removed),
+    ] {
+        colors.push(option(key, hex(color, background)));
+    }
+
+    let text = Style {
+        foreground: Some(base.foreground),
+        background: Some(base.background),
+        ..Style::default()
+    };
+    let styles = [
+        ("TEXT", text),
+        // Syntax
+        ("DEFAULT_KEYWORD", Style::fg(semantic.keyword)),
+        ("DEFAULT_STRING", Style::fg(semantic.string)),
+        ("DEFAULT_NUMBER", Style::fg(semantic.number)),
+        ("DEFAULT_CONSTANT", Style::fg(semantic.constant)),
+        ("DEFAULT_LINE_COMMENT", Style::fg(semantic.comment).italic()),
+        (
+            "DEFAULT_BLOCK_COMMENT",
+            Style::fg(semantic.comment).italic(),
+        ),
+        ("DEFAULT_DOC_COMMENT", Style::fg(semantic.comment).italic()),
+        ("DEFAULT_FUNCTION_DECLARATION", Style::fg(semantic.function)),
+        ("DEFAULT_FUNCTION_CALL", Style::fg(semantic.function)),
+        ("DEFAULT_CLASS_NAME", Style::fg(semantic.r#type)),
+        ("DEFAULT_INTERFACE_NAME", Style::fg(semantic.r#type)),
+        ("DEFAULT_LOCAL_VARIABLE", Style::fg(semantic.variable)),
+        ("DEFAULT_IDENTIFIER", Style::fg(semantic.variable)),
+        ("DEFAULT_VALID_STRING_ESCAPE", Style::fg(semantic.escape)),
+        ("DEFAULT_METADATA", Style::fg(semantic.r#macro)),
+        (
+            "HYPERLINK_ATTRIBUTES",
+            Style {
+                foreground: Some(semantic.link),
+                effect: Some((semantic.link, UNDERSCORE)),
+                ..Style::default()
+            },
+        ),
+        // Search and matching
+        ("SEARCH_RESULT_ATTRIBUTES", Style::bg(state.match_bg)),
+        ("TEXT_SEARCH_RESULT_ATTRIBUTES", Style::bg(state.match_bg)),
+        ("MATCHED_BRACE_ATTRIBUTES", Style::bg(state.match_bg)),
+        // Diagnostics
+        (
+            "ERRORS_ATTRIBUTES",
+            Style::effect(state.error, WAVE_UNDERSCORE),
+        ),
+        (
+            "WARNING_ATTRIBUTES",
+            Style::effect(state.warning, WAVE_UNDERSCORE),
+        ),
+        (
+            "INFO_ATTRIBUTES",
+            Style::effect(state.info, WAVE_UNDERSCORE),
+        ),
+        (
+            "WEAK_WARNING_ATTRIBUTES",
+            Style::effect(state.hint, WAVE_UNDERSCORE),
+        ),
+        // Diff
+        (
+            "DIFF_INSERTED",
+            Style::bg(state.diff_added_bg).stripe(state.diff_added),
+        ),
+        (
+            "DIFF_DELETED",
+            Style::bg(state.diff_removed_bg).stripe(state.diff_removed),
+        ),
+        (
+            "DIFF_MODIFIED",
+            Style::bg(state.active_bg).stripe(state.diff_changed),
+        ),
+        (
+            "DIFF_CONFLICT",
+            Style::bg(state.diff_removed_bg).stripe(state.conflict),
+        ),
+        // Console
+        ("CONSOLE_NORMAL_OUTPUT", Style::fg(base.foreground)),
+        ("CONSOLE_ERROR_OUTPUT", Style::fg(state.error)),
+        ("CONSOLE_BLACK_OUTPUT", Style::fg(ansi.black)),
+        ("CONSOLE_RED_OUTPUT", Style::fg(ansi.red)),
+        ("CONSOLE_GREEN_OUTPUT", Style::fg(ansi.green)),
+        ("CONSOLE_YELLOW_OUTPUT", Style::fg(ansi.yellow)),
+        ("CONSOLE_BLUE_OUTPUT", Style::fg(ansi.blue)),
+        ("CONSOLE_MAGENTA_OUTPUT", Style::fg(ansi.magenta)),
+        ("CONSOLE_CYAN_OUTPUT", Style::fg(ansi.cyan)),
+        ("CONSOLE_GRAY_OUTPUT", Style::fg(ansi.white)),
+        ("CONSOLE_DARKGRAY_OUTPUT", Style::fg(ansi_bright.black)),
+        ("CONSOLE_RED_BRIGHT_OUTPUT", Style::fg(ansi_bright.red)),
+        ("CONSOLE_GREEN_BRIGHT_OUTPUT", Style::fg(ansi_bright.green)),
+        (
+            "CONSOLE_YELLOW_BRIGHT_OUTPUT",
+            Style::fg(ansi_bright.yellow),
+        ),
+        ("CONSOLE_BLUE_BRIGHT_OUTPUT", Style::fg(ansi_bright.blue)),
+        (
+            "CONSOLE_MAGENTA_BRIGHT_OUTPUT",
+            Style::fg(ansi_bright.magenta),
+        ),
+        ("CONSOLE_CYAN_BRIGHT_OUTPUT", Style::fg(ansi_bright.cyan)),
+        ("CONSOLE_WHITE_OUTPUT", Style::fg(ansi_bright.white)),
+    ];
+
+    let mut attributes = Element::new("attributes");
+    for (key, style) in &styles {
+        attributes.push(style.element(key, background));
+    }
+
+    Element::new("scheme")
+        .attr("name", format!("Akari {}", palette.variant.title()))
+        .attr("version", "142")
+        .attr("parent_scheme", if dark { "Darcula" } else { "Default" })
+        .child(colors)
+        .child(attributes)
+        .render()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scheme_header() {
+        let night = generate(&Palette::night());
+        assert!(night.starts_with(
+            "<scheme name=\"Akari Night\" version=\"142\" parent_scheme=\"Darcula\">\n"
+        ));
+        let dawn = generate(&Palette::dawn());
+        assert!(dawn.contains("parent_scheme=\"Default\""));
+    }
+
+    #[test]
+    fn maps_semantic_and_console_keys() {
+        let palette = Palette::night();
+        let scheme = generate(&palette);
+        let background = palette.base.background.rgb();
+
+        let keyword = hex(palette.semantic.keyword, background);
+        assert!(scheme.contains(&format!(
+            "    <option name=\"DEFAULT_KEYWORD\">\n      <value>\n        <option name=\"FOREGROUND\" value=\"{keyword}\"/>\n"
+        )));
+        assert!(scheme.contains("<option name=\"CONSOLE_RED_BRIGHT_OUTPUT\">"));
+        assert!(scheme.contains("<option name=\"DIFF_INSERTED\">"));
+        assert!(scheme.contains("<option name=\"EFFECT_TYPE\" value=\"2\"/>"));
+    }
+
+    #[test]
+    fn translucent_colors_are_blended() {
+        let background: Rgb = "#000000".parse().unwrap();
+        let white: Rgba = "#FFFFFF".parse().unwrap();
+        assert_eq!(hex(white, background), "FFFFFF");
+        assert_eq!(hex(white.with_alpha(0.5), background), "808080");
+    }
+}
@@ -1,15 +1,62 @@
+#[cfg(feature = "generator")]
+mod ase;
 mod color;
+mod contrast;
+mod diff;
+mod explain;
+mod export;
+#[cfg(feature = "generator")]
+mod exporter;
+mod expr;
 #[cfg(feature = "generator")]
 mod generator;
+#[cfg(feature = "generator")]
+mod gpl;
+#[cfg(feature = "generator")]
+mod iterm;
+#[cfg(feature = "generator")]
+mod jetbrains;
+mod lint;
+#[cfg(feature = "generator")]
+mod output;
 mod palette;
 #[cfg(feature = "generator")]
+mod schema;
+#[cfg(feature = "generator")]
 pub mod terminal;
+#[cfg(feature = "generator")]
+mod watch;
+#[cfg(feature = "generator")]
+mod xcode;
+#[cfg(feature = "generator")]
+mod xml;
 
-pub use color::Rgb;
+pub use color::{Oklab, Oklch, Rgb, Rgba};
+pub use contrast::{ContrastCheck, WCAG_AA, WCAG_AAA};
+pub use diff::ColorChange;
+pub use explain::{Derivation, Explanation};
+pub use export::ExportFormat;
+#[cfg(feature = "generator")]
+pub use exporter::{
+    AseExporter, Exporter, GplExporter, ItermExporter, JetBrainsExporter, TerminalExporter,
+    XcodeExporter,
+};
+pub use expr::ExprError;
 #[cfg(feature = "generator")]
 pub use generator::Generator;
+pub use lint::{ContrastRule, Direction, LintRules, LintViolation, MonotonicRule};
+#[cfg(feature = "generator")]
+pub use output::{
+    CACHE, Cache, Drift, MANIFEST, Manifest, check_artifacts, prune_files, write_artifacts,
+};
 pub use palette::Palette;
+#[cfg(feature = "generator")]
+pub use schema::palette_schema;
+#[cfg(feature = "generator")]
+pub use watch::Watcher;
 
+use std::borrow::Cow;
+use std::path::Path;
 #[cfg(feature = "generator")]
 use std::path::PathBuf;
 
@@ -21,6 +68,18 @@ pub enum Error {
     ParsePalette(#[from] toml::de::Error),
     #[error("unresolved reference: {0}")]
     UnresolvedRef(String),
+    #[error("reference cycle: {}", .0.join(" -> "))]
+    CyclicRef(Vec<String>),
+    #[error("palette to extend not found: {0}")]
+    ParentNotFound(String),
+    #[error("unknown palette key: {0}")]
+    UnknownKey(String),
+    #[error("invalid palette key: {0} (expected letters, digits and _, not starting with a digit)")]
+    InvalidKey(String),
+    #[error("unknown variant: {0}")]
+    UnknownVariant(String),
+    #[error("unknown format: {0} (expected one of: json, toml, yaml, env)")]
+    UnknownFormat(String),
     #[cfg(feature = "generator")]
     #[error("template {context}: {source}")]
     Template {
@@ -31,6 +90,12 @@ pub enum Error {
     #[error("invalid hex color: {0}")]
     InvalidHex(String),
     #[cfg(feature = "generator")]
+    #[error("unknown tool: {name} (expected all or one of: {})", .available.join(", "))]
+    UnknownTool {
+        name: String,
+        available: Vec<String>,
+    },
+    #[cfg(feature = "generator")]
     #[error("non-UTF-8 path: {0}")]
     InvalidPath(PathBuf),
     #[cfg(feature = "generator")]
@@ -42,43 +107,61 @@ pub enum Error {
     #[cfg(feature = "generator")]
     #[error("plist output was not valid UTF-8")]
     PlistUtf8,
+    #[error("invalid lint rules: {0}")]
+    ParseLintRules(toml::de::Error),
     #[error("invalid color expression: {0}")]
-    InvalidColorExpr(String),
+    InvalidColorExpr(Box<ExprError>),
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
-pub enum Variant {
-    Night,
-    Dawn,
-}
+/// Palette variant, named after the `akari-{name}.toml` file it is loaded from.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize)]
+#[serde(transparent)]
+pub struct Variant(Cow<'static, str>);
 
 impl Variant {
+    pub const NIGHT: Self = Self(Cow::Borrowed("night"));
+    pub const DAWN: Self = Self(Cow::Borrowed("dawn"));
+
     #[must_use]
-    pub const fn name(self) -> &'static str {
-        match self {
-            Self::Night => "night",
-            Self::Dawn => "dawn",
-        }
+    pub fn new(name: impl Into<String>) -> Self {
+        Self(Cow::Owned(name.into()))
     }
 
+    /// Variant named by a palette file path (`palette/akari-dusk.toml` -> `dusk`).
     #[must_use]
-    pub const fn title(self) -> &'static str {
-        match self {
-            Self::Night => "Night",
-            Self::Dawn => "Dawn",
+    pub fn from_palette_path(path: &Path) -> Option<Self> {
+        if path.extension()? != "toml" {
+            return None;
         }
+        let name = path.file_stem()?.to_str()?.strip_prefix("akari-")?;
+        (!name.is_empty()).then(|| Self::new(name))
     }
 
     #[must_use]
-    pub const fn palette_filename(self) -> &'static str {
-        match self {
-            Self::Night => "akari-night.toml",
-            Self::Dawn => "akari-dawn.toml",
-        }
+    pub fn name(&self) -> &str {
+        &self.0
     }
-}
 
-pub const VARIANTS: [Variant; 2] = [Variant::Night, Variant::Dawn];
+    /// Name with each hyphen-separated word capitalized (`high-contrast` -> `High-Contrast`).
+    #[must_use]
+    pub fn title(&self) -> String {
+        self.0
+            .split('-')
+            .map(|word| {
+                let mut chars = word.chars();
+                chars.next().map_or_else(String::new, |first| {
+                    first.to_uppercase().chain(chars).collect()
+                })
+            })
+            .collect::<Vec<_>>()
+            .join("-")
+    }
+
+    #[must_use]
+    pub fn palette_filename(&self) -> String {
+        format!("akari-{}.toml", self.0)
+    }
+}
 
 #[cfg(feature = "generator")]
 /// Content of an artifact
@@ -86,6 +169,8 @@ pub const VARIANTS: [Variant; 2] = [Variant::Night, Variant::Dawn];
 pub enum ArtifactContent {
     /// Text content to be written
     Text(String),
+    /// Binary content to be written
+    Binary(Vec<u8>),
     /// Source path to be copied
     Copy(PathBuf),
 }
@@ -110,6 +195,14 @@ impl Artifact {
         }
     }
 
+    #[must_use]
+    pub fn binary(rel_path: impl Into<PathBuf>, content: impl Into<Vec<u8>>) -> Self {
+        Self {
+            rel_path: rel_path.into(),
+            content: ArtifactContent::Binary(content.into()),
+        }
+    }
+
     #[must_use]
     pub fn copy(rel_path: impl Into<PathBuf>, src: impl Into<PathBuf>) -> Self {
         Self {
@@ -133,3 +226,30 @@ pub fn find_project_root() -> Result<PathBuf, Error> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn variant_from_palette_path() {
+        let variant = Variant::from_palette_path(Path::new("palette/akari-night.toml"));
+        assert_eq!(variant, Some(Variant::NIGHT));
+        assert_eq!(
+            Variant::from_palette_path(Path::new("palette/lint.toml")),
+            None
+        );
+        assert_eq!(Variant::from_palette_path(Path::new("akari-.toml")), None);
+        assert_eq!(
+            Variant::from_palette_path(Path::new("akari-dusk.json")),
+            None
+        );
+    }
+
+    #[test]
+    fn variant_title() {
+        assert_eq!(Variant::NIGHT.title(), "Night");
+        assert_eq!(Variant::new("high-contrast").title(), "High-Contrast");
+        assert_eq!(Variant::DAWN.palette_filename(), "akari-dawn.toml");
+    }
+}
@@ -0,0 +1,271 @@
+use crate::{ContrastCheck, Error, Palette, Rgba};
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Accessibility rules checked against resolved palettes.
+///
+/// Loaded from TOML (see `palette/lint.toml`):
+///
+/// ```toml
+/// [[contrast]]
+/// foreground = ["semantic.*"]
+/// background = "base.background"
+/// min_ratio = 4.5
+///
+/// [[monotonic]]
+/// keys = ["layers.base", "layers.surface", "layers.border"]
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LintRules {
+    #[serde(default)]
+    pub contrast: Vec<ContrastRule>,
+    #[serde(default)]
+    pub monotonic: Vec<MonotonicRule>,
+}
+
+/// Every matching foreground must reach `min_ratio` (WCAG 2.x) on `background`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ContrastRule {
+    /// Key paths or `section.*` patterns (e.g. "semantic.*", "state.hint")
+    pub foreground: Vec<String>,
+    /// Key path of the background
+    pub background: String,
+    pub min_ratio: f64,
+}
+
+/// Keys must be strictly ordered by perceived (OKLCH) lightness.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MonotonicRule {
+    pub keys: Vec<String>,
+    #[serde(default)]
+    pub direction: Direction,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    /// Lightness must increase along `keys`
+    Increasing,
+    /// Lightness must decrease along `keys`
+    Decreasing,
+    /// Either direction, as long as it is consistent (dark and light variants flip)
+    #[default]
+    Any,
+}
+
+impl FromStr for LintRules {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        toml::from_str(s).map_err(Error::ParseLintRules)
+    }
+}
+
+/// A single rule violation for one key.
+#[derive(Debug, Clone)]
+pub struct LintViolation {
+    /// Key path that violates the rule
+    pub key: String,
+    pub message: String,
+}
+
+impl LintRules {
+    /// Load rules from a file path.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, Error> {
+        fs::read_to_string(path)?.parse()
+    }
+
+    /// Check every rule against a palette, returning all violations.
+    pub fn check(&self, palette: &Palette) -> Result<Vec<LintViolation>, Error> {
+        let mut violations = Vec::new();
+        for rule in &self.contrast {
+            violations.extend(rule.check(palette)?);
+        }
+        for rule in &self.monotonic {
+            violations.extend(rule.check(palette)?);
+        }
+        Ok(violations)
+    }
+}
+
+impl ContrastRule {
+    fn check(&self, palette: &Palette) -> Result<Vec<LintViolation>, Error> {
+        let bg_color = lookup(palette, &self.background)?;
+        let mut violations = Vec::new();
+
+        for pattern in &self.foreground {
+            for (key, fg_color) in matching(palette, pattern)? {
+                let check = ContrastCheck::new(&key, fg_color, &self.background, bg_color);
+                if !check.meets(self.min_ratio) {
+                    violations.push(LintViolation {
+                        message: format!(
+                            "contrast {:.2}:1 on {} (min {}:1)",
+                            check.ratio, self.background, self.min_ratio
+                        ),
+                        key,
+                    });
+                }
+            }
+        }
+
+        Ok(violations)
+    }
+}
+
+impl MonotonicRule {
+    fn check(&self, palette: &Palette) -> Result<Vec<LintViolation>, Error> {
+        let lightness = self
+            .keys
+            .iter()
+            .map(|key| Ok(lookup(palette, key)?.rgb().to_oklch().l))
+            .collect::<Result<Vec<f64>, Error>>()?;
+
+        let direction = match self.direction {
+            Direction::Any => match lightness.as_slice() {
+                [first, second, ..] if second < first => Direction::Decreasing,
+                _ => Direction::Increasing,
+            },
+            direction => direction,
+        };
+
+        let mut violations = Vec::new();
+        for (i, pair) in lightness.windows(2).enumerate() {
+            let ordered = match direction {
+                Direction::Decreasing => pair[1] < pair[0],
+                _ => pair[1] > pair[0],
+            };
+            if !ordered {
+                let word = if direction == Direction::Decreasing {
+                    "darker"
+                } else {
+                    "lighter"
+                };
+                violations.push(LintViolation {
+                    key: self.keys[i + 1].clone(),
+                    message: format!(
+                        "lightness {:.3} is not {word} than {} ({:.3})",
+                        pair[1], self.keys[i], pair[0]
+                    ),
+                });
+            }
+        }
+
+        Ok(violations)
+    }
+}
+
+fn lookup(palette: &Palette, key: &str) -> Result<Rgba, Error> {
+    palette
+        .get(key)
+        .ok_or_else(|| Error::UnresolvedRef(key.to_string()))
+}
+
+/// Resolve a key path or `section.*` pattern to matching entries.
+fn matching(palette: &Palette, pattern: &str) -> Result<Vec<(String, Rgba)>, Error> {
+    let Some(prefix) = pattern.strip_suffix('*') else {
+        return Ok(vec![(pattern.to_string(), lookup(palette, pattern)?)]);
+    };
+
+    let entries: Vec<_> = palette
+        .iter()
+        .filter(|(key, _)| key.starts_with(prefix))
+        .collect();
+    if entries.is_empty() {
+        return Err(Error::UnresolvedRef(pattern.to_string()));
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contrast_rule_reports_each_failing_key() {
+        let rules = LintRules::from_str(
+            r#"
+[[contrast]]
+foreground = ["semantic.*"]
+background = "base.background"
+min_ratio = 21.0
+"#,
+        )
+        .unwrap();
+        let violations = rules.check(&Palette::night()).unwrap();
+        assert_eq!(violations.len(), 16);
+        assert!(violations.iter().any(|v| v.key == "semantic.keyword"));
+    }
+
+    #[test]
+    fn contrast_rule_passes() {
+        let rules = LintRules::from_str(
+            r#"
+[[contrast]]
+foreground = ["base.foreground"]
+background = "base.background"
+min_ratio = 4.5
+"#,
+        )
+        .unwrap();
+        assert!(rules.check(&Palette::night()).unwrap().is_empty());
+        assert!(rules.check(&Palette::dawn()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn monotonic_any_accepts_both_variants() {
+        let rules = LintRules::from_str(
+            r#"
+[[monotonic]]
+keys = ["layers.base", "layers.surface", "layers.border"]
+"#,
+        )
+        .unwrap();
+        assert!(rules.check(&Palette::night()).unwrap().is_empty());
+        assert!(rules.check(&Palette::dawn()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn monotonic_direction_is_enforced() {
+        let rules = LintRules::from_str(
+            r#"
+[[monotonic]]
+keys = ["layers.base", "layers.surface"]
+direction = "decreasing"
+"#,
+        )
+        .unwrap();
+        let violations = rules.check(&Palette::night()).unwrap();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].key, "layers.surface");
+    }
+
+    #[test]
+    fn unknown_key_fails() {
+        let rules = LintRules::from_str(
+            r#"
+[[contrast]]
+foreground = ["semantic.nonexistent"]
+background = "base.background"
+min_ratio = 4.5
+"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            rules.check(&Palette::night()),
+            Err(Error::UnresolvedRef(_))
+        ));
+    }
+
+    #[test]
+    fn unknown_rule_field_fails() {
+        let err = "[[contrast]]\nforground = []\n"
+            .parse::<LintRules>()
+            .unwrap_err();
+        assert!(matches!(err, Error::ParseLintRules(_)));
+    }
+}
@@ -1,7 +1,16 @@
-use akari_theme::{ArtifactContent, Generator, Palette, Variant, find_project_root};
+use akari_theme::{
+    Cache, ColorChange, ExportFormat, Generator, LintRules, Manifest, Palette, Rgba, Variant,
+    Watcher, check_artifacts, find_project_root, prune_files, write_artifacts,
+};
 use clap::{Parser, Subcommand};
 use std::fs;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
 use std::process::ExitCode;
+use std::time::Duration;
+
+/// How often `watch` looks for changed files.
+const POLL_INTERVAL: Duration = Duration::from_millis(300);
 
 #[derive(Parser)]
 #[command(name = "akari-gen")]
@@ -22,69 +31,424 @@ enum Command {
         /// Output directory (defaults to dist/)
         #[arg(long)]
         out_dir: Option<std::path::PathBuf>,
+
+        /// Verify the output directory is up to date instead of writing
+        #[arg(long)]
+        check: bool,
+
+        /// Delete previously generated files that are no longer produced
+        #[arg(long, conflicts_with = "check")]
+        prune: bool,
+
+        /// Regenerate every tool, even those whose inputs are unchanged
+        #[arg(long)]
+        force: bool,
+    },
+    /// Regenerate a tool whenever its templates or the palettes change
+    Watch {
+        /// Target tool (or 'all' to watch every tool)
+        #[arg(long)]
+        tool: String,
+
+        /// Output directory (defaults to dist/)
+        #[arg(long)]
+        out_dir: Option<std::path::PathBuf>,
+    },
+    /// Show how a palette color was derived
+    Explain {
+        /// Key path (e.g. semantic.comment)
+        key: String,
+
+        /// Variant to explain (defaults to all variants)
+        #[arg(long)]
+        variant: Option<String>,
+    },
+    /// Export a resolved palette as flat key/value pairs
+    Export {
+        /// Output format: json, toml, yaml or env
+        #[arg(long)]
+        format: ExportFormat,
+
+        /// Variant to export (e.g. night)
+        #[arg(long)]
+        variant: String,
+
+        /// Include the source expression of each color
+        #[arg(long)]
+        expressions: bool,
+
+        /// Output file (defaults to stdout)
+        #[arg(long, short)]
+        output: Option<std::path::PathBuf>,
+    },
+    /// Show resolved colors that differ between two palettes
+    Diff {
+        /// Old palette: a file path or a git `REV:PATH` (e.g. HEAD:palette/akari-night.toml)
+        old: String,
+
+        /// New palette: a file path or a git `REV:PATH`
+        new: String,
+
+        /// Print changes as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Print the JSON Schema for palette files
+    Schema {
+        /// Output file (defaults to stdout)
+        #[arg(long, short)]
+        output: Option<std::path::PathBuf>,
+    },
+    /// Check palettes against accessibility rules
+    Lint {
+        /// Rules file (defaults to palette/lint.toml)
+        #[arg(long)]
+        rules: Option<std::path::PathBuf>,
     },
 }
 
 fn main() -> ExitCode {
-    if let Err(e) = run() {
-        eprintln!("error: {e}");
-        return ExitCode::FAILURE;
+    match run() {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
     }
-    ExitCode::SUCCESS
 }
 
-fn run() -> Result<(), akari_theme::Error> {
+fn run() -> Result<ExitCode, akari_theme::Error> {
     let cli = Cli::parse();
 
     match cli.command {
-        Command::Generate { tool, out_dir } => {
+        Command::Generate {
+            tool,
+            out_dir,
+            check,
+            prune,
+            force,
+        } => {
             let root = find_project_root()?;
             let out_root = out_dir.unwrap_or_else(|| root.join("dist"));
 
             // Load palettes
-            let palette_dir = root.join("palette");
-            let night = Palette::from_path(
-                palette_dir.join(Variant::Night.palette_filename()),
-                Variant::Night,
-            )?;
-            let dawn = Palette::from_path(
-                palette_dir.join(Variant::Dawn.palette_filename()),
-                Variant::Dawn,
-            )?;
+            let palettes = Palette::discover(root.join("palette"))?;
 
             let generator = Generator::new(root.join("templates"))?;
 
-            // Get tools to generate
-            let tools: Vec<String> = if tool == "all" {
-                generator.available_tools()?
-            } else {
-                vec![tool]
-            };
+            let (tools, scan_dirs) = tool_scope(&generator, &tool)?;
 
-            // Generate and write artifacts
-            for tool_name in &tools {
-                let artifacts = generator.generate_tool(tool_name, &night, &dawn)?;
-                for artifact in artifacts {
-                    let output_path = out_root.join(&artifact.rel_path);
+            if check {
+                let artifacts = generator.generate_tools(&tools, &palettes)?;
+                let drifts = check_artifacts(&out_root, &artifacts, &scan_dirs)?;
+                for drift in &drifts {
+                    println!("{drift}");
+                }
+                if !drifts.is_empty() {
+                    eprintln!(
+                        "{} file(s) out of date; regenerate with `akari-gen generate`",
+                        drifts.len()
+                    );
+                    return Ok(ExitCode::FAILURE);
+                }
+                println!("{} is up to date", out_root.display());
+                return Ok(ExitCode::SUCCESS);
+            }
 
-                    // Ensure parent directory exists
-                    if let Some(parent) = output_path.parent() {
-                        fs::create_dir_all(parent)?;
-                    }
+            write_generated(
+                &out_root, &generator, &palettes, tools, &scan_dirs, force, prune,
+            )?;
+        }
+        Command::Watch { tool, out_dir } => {
+            let root = find_project_root()?;
+            let out_root = out_dir.unwrap_or_else(|| root.join("dist"));
+            let palette_dir = root.join("palette");
+            let templates_dir = root.join("templates");
+            let tool_dir = if tool == "all" {
+                templates_dir.clone()
+            } else {
+                templates_dir.join(&tool)
+            };
 
-                    match &artifact.content {
-                        ArtifactContent::Text(content) => {
-                            fs::write(&output_path, content)?;
-                        }
-                        ArtifactContent::Copy(src) => {
-                            fs::copy(src, &output_path)?;
+            // An unknown tool is fatal. A broken template at startup is reported
+            // like any other, and the generator is created again on the next change.
+            let mut generator = Generator::new(&templates_dir).ok();
+            if let Some(generator) = &generator {
+                tool_scope(generator, &tool)?;
+            }
+            let mut regenerate = |templates_changed: bool| -> Result<(), akari_theme::Error> {
+                let generator = match &mut generator {
+                    Some(generator) => {
+                        if templates_changed {
+                            generator.reload()?;
                         }
+                        generator
                     }
-                    println!("  {}", artifact.rel_path.display());
+                    None => generator.insert(Generator::new(&templates_dir)?),
+                };
+                let palettes = Palette::discover(&palette_dir)?;
+                let (tools, scan_dirs) = tool_scope(generator, &tool)?;
+                write_generated(
+                    &out_root, generator, &palettes, tools, &scan_dirs, false, false,
+                )
+            };
+
+            let mut watcher = Watcher::new([&palette_dir, &tool_dir]);
+            println!(
+                "watching {} and {} (Ctrl-C to stop)",
+                palette_dir.display(),
+                tool_dir.display()
+            );
+            if let Err(e) = regenerate(false) {
+                eprintln!("error: {e}");
+            }
+
+            loop {
+                std::thread::sleep(POLL_INTERVAL);
+                let changes = watcher.changes();
+                if changes.is_empty() {
+                    continue;
+                }
+                for path in &changes {
+                    println!(
+                        "changed: {}",
+                        path.strip_prefix(&root).unwrap_or(path).display()
+                    );
+                }
+                let templates_changed = changes.iter().any(|p| p.starts_with(&tool_dir));
+                if let Err(e) = regenerate(templates_changed) {
+                    eprintln!("error: {e}");
                 }
             }
         }
+        Command::Explain { key, variant } => {
+            let root = find_project_root()?;
+            let mut palettes = Palette::discover(root.join("palette"))?;
+            if let Some(variant) = variant {
+                palettes.retain(|p| p.variant.name() == variant);
+                if palettes.is_empty() {
+                    return Err(akari_theme::Error::UnknownVariant(variant));
+                }
+            }
+
+            for palette in &palettes {
+                let explanation = palette
+                    .explain(&key)
+                    .ok_or_else(|| akari_theme::Error::UnknownKey(key.clone()))?;
+                if palettes.len() > 1 {
+                    println!("{}:", palette.name);
+                }
+                print!("{explanation}");
+            }
+        }
+        Command::Export {
+            format,
+            variant,
+            expressions,
+            output,
+        } => {
+            let root = find_project_root()?;
+            let palette = Palette::discover(root.join("palette"))?
+                .into_iter()
+                .find(|p| p.variant.name() == variant)
+                .ok_or(akari_theme::Error::UnknownVariant(variant))?;
+
+            let content = palette.export(format, expressions);
+            match output {
+                Some(path) => fs::write(path, content)?,
+                None => print!("{content}"),
+            }
+        }
+        Command::Diff { old, new, json } => {
+            let changes = load_palette(&old)?.diff(&load_palette(&new)?);
+            if json {
+                let content =
+                    serde_json::to_string_pretty(&changes).map_err(std::io::Error::other)?;
+                println!("{content}");
+            } else {
+                print_changes(&changes, std::io::stdout().is_terminal());
+            }
+        }
+        Command::Schema { output } => {
+            let schema = akari_theme::palette_schema();
+            let content = format!("{schema:#}\n");
+            match output {
+                Some(path) => fs::write(path, content)?,
+                None => print!("{content}"),
+            }
+        }
+        Command::Lint { rules } => {
+            let root = find_project_root()?;
+            let palette_dir = root.join("palette");
+            let rules =
+                LintRules::from_path(rules.unwrap_or_else(|| palette_dir.join("lint.toml")))?;
+
+            let mut failed = false;
+            for palette in Palette::discover(&palette_dir)? {
+                let violations = rules.check(&palette)?;
+
+                if violations.is_empty() {
+                    println!("{}: ok", palette.name);
+                    continue;
+                }
+
+                failed = true;
+                println!("{}: {} violation(s)", palette.name, violations.len());
+                for violation in violations {
+                    println!("  {}: {}", violation.key, violation.message);
+                }
+            }
+
+            if failed {
+                return Ok(ExitCode::FAILURE);
+            }
+        }
     }
 
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Tools to generate and the output directories they own.
+///
+/// With `all`, anything else in the output directory is stale. Any other name
+/// must be one of the generator's available tools.
+fn tool_scope(
+    generator: &Generator,
+    tool: &str,
+) -> Result<(Vec<String>, Vec<PathBuf>), akari_theme::Error> {
+    let available = generator.available_tools()?;
+    if tool == "all" {
+        Ok((available, vec![PathBuf::new()]))
+    } else if available.iter().any(|name| name == tool) {
+        Ok((vec![tool.to_string()], vec![PathBuf::from(tool)]))
+    } else {
+        Err(akari_theme::Error::UnknownTool {
+            name: tool.to_string(),
+            available,
+        })
+    }
+}
+
+/// Render `tools` and write whatever changed, updating the manifest and cache.
+fn write_generated(
+    out_root: &Path,
+    generator: &Generator,
+    palettes: &[Palette],
+    tools: Vec<String>,
+    scan_dirs: &[PathBuf],
+    force: bool,
+    prune: bool,
+) -> Result<(), akari_theme::Error> {
+    // Skip tools whose inputs are unchanged and whose outputs are intact.
+    // Pruning needs every tool's full output, so it renders everything.
+    let mut manifest = Manifest::load(out_root)?;
+    let mut cache = Cache::load(out_root);
+    let mut pending = Vec::new();
+    let mut produced = Vec::new();
+    for tool_name in tools {
+        let hash = generator.fingerprint(&tool_name, palettes)?;
+        if !force && !prune && cache.is_fresh(out_root, &tool_name, hash) {
+            produced.extend(cache.outputs(&tool_name).cloned());
+        } else {
+            pending.push((tool_name, hash));
+        }
+    }
+
+    let pending_tools: Vec<String> = pending.iter().map(|(t, _)| t.clone()).collect();
+    let artifacts = generator.generate_tools(&pending_tools, palettes)?;
+    let written = write_artifacts(out_root, &artifacts)?;
+    for (tool_name, hash) in pending {
+        let outputs = artifacts
+            .iter()
+            .filter(|artifact| artifact.rel_path.starts_with(&tool_name));
+        cache.insert(&tool_name, hash, outputs)?;
+    }
+    for path in &written {
+        println!("  {}", path.display());
+    }
+    println!(
+        "{} written, {} unchanged",
+        written.len(),
+        produced.len() + artifacts.len() - written.len()
+    );
+    produced.extend(artifacts.into_iter().map(|a| a.rel_path));
+
+    let stale = manifest.record(&produced, scan_dirs);
+    if prune {
+        prune_files(out_root, &stale)?;
+        manifest.forget(&stale);
+        for path in &stale {
+            println!("- {}", path.display());
+        }
+    } else if !stale.is_empty() {
+        eprintln!(
+            "{} stale file(s) no longer generated; remove them with --prune",
+            stale.len()
+        );
+    }
+    manifest.save(out_root)?;
+
+    cache.save(out_root)?;
     Ok(())
 }
+
+/// Load a palette from a file path, or from git when given `REV:PATH`.
+///
+/// Relative `extends` paths in a git revision resolve against the current directory.
+fn load_palette(spec: &str) -> Result<Palette, akari_theme::Error> {
+    let variant_for = |path: &Path| {
+        Variant::from_palette_path(path)
+            .unwrap_or_else(|| Variant::new(path.file_stem().unwrap_or_default().to_string_lossy()))
+    };
+
+    let path = Path::new(spec);
+    if path.is_file() {
+        return Palette::from_path(path, variant_for(path));
+    }
+    let Some((_, file)) = spec.split_once(':') else {
+        return Palette::from_path(path, variant_for(path));
+    };
+
+    let output = std::process::Command::new("git")
+        .args(["show", spec])
+        .output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(std::io::Error::other(stderr.trim().to_string()).into());
+    }
+    let content = String::from_utf8(output.stdout).map_err(std::io::Error::other)?;
+    Palette::from_str(&content, variant_for(Path::new(file)))
+}
+
+fn print_changes(changes: &[ColorChange], swatches: bool) {
+    if changes.is_empty() {
+        println!("no changes");
+        return;
+    }
+
+    let show = |color: Option<Rgba>| match color {
+        Some(color) if swatches => {
+            let Rgba { r, g, b, .. } = color;
+            format!("{color:<9} \x1b[48;2;{r};{g};{b}m    \x1b[0m")
+        }
+        Some(color) => format!("{color:<9}"),
+        None => format!("{:<9}", "-"),
+    };
+    let width = changes.iter().map(|c| c.key.len()).max().unwrap_or(0);
+
+    for change in changes {
+        let mut delta = change
+            .delta_e
+            .map(|d| format!("  ΔE {d:.3}"))
+            .unwrap_or_default();
+        if let Some(d) = change.delta_alpha.filter(|d| *d != 0.0) {
+            delta.push_str(&format!("  α {d:+.2}"));
+        }
+        println!(
+            "{:<width$}  {} → {}{delta}",
+            change.key,
+            show(change.old),
+            show(change.new)
+        );
+    }
+}
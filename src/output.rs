@@ -0,0 +1,553 @@
+use crate::{Artifact, ArtifactContent, Error};
+use similar::TextDiff;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fmt;
+use std::fs;
+use std::hash::Hasher;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Difference between generated artifacts and the files in the output directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Drift {
+    /// Artifact not present on disk
+    Missing(PathBuf),
+    /// File on disk differs from the artifact; `diff` is a unified diff
+    /// (empty for binary content)
+    Changed { path: PathBuf, diff: String },
+    /// File on disk that no artifact produces
+    Stale(PathBuf),
+}
+
+impl fmt::Display for Drift {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing(path) => write!(f, "missing: {}", path.display()),
+            Self::Changed { path, diff } if diff.is_empty() => {
+                write!(f, "changed: {} (binary files differ)", path.display())
+            }
+            Self::Changed { path, diff } => write!(f, "changed: {}\n{diff}", path.display()),
+            Self::Stale(path) => write!(f, "stale: {}", path.display()),
+        }
+    }
+}
+
+impl Artifact {
+    /// Bytes this artifact writes (reads the source of a copied file).
+    pub fn bytes(&self) -> Result<Vec<u8>, Error> {
+        match &self.content {
+            ArtifactContent::Text(text) => Ok(text.clone().into_bytes()),
+            ArtifactContent::Binary(bytes) => Ok(bytes.clone()),
+            ArtifactContent::Copy(src) => Ok(fs::read(src)?),
+        }
+    }
+}
+
+/// Write artifacts under `out_root`, creating directories as needed.
+///
+/// Files whose content is already identical are left untouched (keeping their
+/// mtime). Returns the paths that were actually written.
+pub fn write_artifacts(out_root: &Path, artifacts: &[Artifact]) -> Result<Vec<PathBuf>, Error> {
+    let mut written = Vec::new();
+    for artifact in artifacts {
+        let output_path = out_root.join(&artifact.rel_path);
+        if fs::read(&output_path)
+            .is_ok_and(|existing| artifact.bytes().is_ok_and(|expected| existing == expected))
+        {
+            continue;
+        }
+
+        // Ensure parent directory exists
+        if let Some(parent) = output_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        match &artifact.content {
+            ArtifactContent::Text(content) => {
+                fs::write(&output_path, content)?;
+            }
+            ArtifactContent::Binary(content) => {
+                fs::write(&output_path, content)?;
+            }
+            ArtifactContent::Copy(src) => {
+                fs::copy(src, &output_path)?;
+            }
+        }
+        written.push(artifact.rel_path.clone());
+    }
+    Ok(written)
+}
+
+/// File in the output root listing every generated path, used to prune stale files.
+pub const MANIFEST: &str = ".akari-manifest";
+
+/// Paths written by previous runs, relative to the output root.
+///
+/// Only files listed here are ever pruned, so hand-written files in the output
+/// directory are left alone.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Manifest {
+    paths: BTreeSet<PathBuf>,
+}
+
+impl Manifest {
+    /// Load the manifest from `out_root`; empty if there is none yet.
+    pub fn load(out_root: &Path) -> Result<Self, Error> {
+        let content = match fs::read_to_string(out_root.join(MANIFEST)) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e.into()),
+        };
+        let paths = content
+            .lines()
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(PathBuf::from)
+            .collect();
+        Ok(Self { paths })
+    }
+
+    pub fn save(&self, out_root: &Path) -> Result<(), Error> {
+        let mut content =
+            String::from("# Generated by akari-gen; lists files `generate --prune` may remove\n");
+        for path in &self.paths {
+            content.push_str(&path.to_string_lossy().replace('\\', "/"));
+            content.push('\n');
+        }
+        fs::create_dir_all(out_root)?;
+        fs::write(out_root.join(MANIFEST), content)?;
+        Ok(())
+    }
+
+    /// Record the paths produced by a run covering `scan_dirs` (as in [`check_artifacts`]).
+    ///
+    /// Returns previously generated paths in scope that this run no longer
+    /// produces. They stay listed until [`Manifest::forget`] is called.
+    pub fn record(&mut self, produced: &[PathBuf], scan_dirs: &[PathBuf]) -> Vec<PathBuf> {
+        let produced: BTreeSet<PathBuf> = produced.iter().cloned().collect();
+        let stale = self
+            .paths
+            .iter()
+            .filter(|path| scan_dirs.iter().any(|dir| path.starts_with(dir)))
+            .filter(|path| !produced.contains(*path))
+            .cloned()
+            .collect();
+        self.paths.extend(produced);
+        stale
+    }
+
+    pub fn forget(&mut self, paths: &[PathBuf]) {
+        for path in paths {
+            self.paths.remove(path);
+        }
+    }
+}
+
+/// File in the output root with content hashes per tool, used to skip tools
+/// whose palettes and templates are unchanged. Not meant to be committed.
+pub const CACHE: &str = ".akari-cache";
+
+/// Content hashes of the inputs each tool was last generated from, and of the
+/// files it wrote.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Cache {
+    tools: BTreeMap<String, CacheEntry>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct CacheEntry {
+    inputs: u64,
+    outputs: BTreeMap<PathBuf, u64>,
+}
+
+impl Cache {
+    /// Load the cache from `out_root`; empty if there is none or it is unreadable.
+    ///
+    /// Each tool line (`<tool> <inputs hash>`) is followed by indented
+    /// `<content hash> <path>` lines for the files it wrote.
+    #[must_use]
+    pub fn load(out_root: &Path) -> Self {
+        let Ok(content) = fs::read_to_string(out_root.join(CACHE)) else {
+            return Self::default();
+        };
+        let mut tools = BTreeMap::new();
+        let mut current: Option<&mut CacheEntry> = None;
+        for line in content.lines().filter(|line| !line.starts_with('#')) {
+            if let Some(output) = line.strip_prefix("  ") {
+                let parsed = output.split_once(' ').and_then(|(hash, path)| {
+                    Some((PathBuf::from(path), u64::from_str_radix(hash, 16).ok()?))
+                });
+                if let (Some(entry), Some((path, hash))) = (current.as_deref_mut(), parsed) {
+                    entry.outputs.insert(path, hash);
+                }
+            } else {
+                current = line.split_once(' ').and_then(|(tool, hash)| {
+                    let inputs = u64::from_str_radix(hash, 16).ok()?;
+                    let entry = CacheEntry {
+                        inputs,
+                        outputs: BTreeMap::new(),
+                    };
+                    Some(tools.entry(tool.to_string()).insert_entry(entry).into_mut())
+                });
+            }
+        }
+        Self { tools }
+    }
+
+    pub fn save(&self, out_root: &Path) -> Result<(), Error> {
+        let mut content = String::from("# Generated by akari-gen; safe to delete\n");
+        for (tool, entry) in &self.tools {
+            content.push_str(&format!("{tool} {:016x}\n", entry.inputs));
+            for (path, hash) in &entry.outputs {
+                content.push_str(&format!("  {hash:016x} {}\n", path.display()));
+            }
+        }
+        fs::create_dir_all(out_root)?;
+        fs::write(out_root.join(CACHE), content)?;
+        Ok(())
+    }
+
+    /// Whether `tool` was last generated from inputs hashing to `hash` and
+    /// every file it wrote under `out_root` still has the content it was
+    /// written with.
+    #[must_use]
+    pub fn is_fresh(&self, out_root: &Path, tool: &str, hash: u64) -> bool {
+        self.tools.get(tool).is_some_and(|entry| {
+            entry.inputs == hash
+                && !entry.outputs.is_empty()
+                && entry.outputs.iter().all(|(path, expected)| {
+                    fs::read(out_root.join(path))
+                        .is_ok_and(|bytes| content_hash(&bytes) == *expected)
+                })
+        })
+    }
+
+    /// Paths `tool` wrote when it was last generated (relative to the output root).
+    pub fn outputs(&self, tool: &str) -> impl Iterator<Item = &PathBuf> {
+        self.tools
+            .get(tool)
+            .into_iter()
+            .flat_map(|entry| entry.outputs.keys())
+    }
+
+    /// Record that `tool` was generated from inputs hashing to `hash` and
+    /// produced `artifacts`, replacing what was recorded before.
+    pub fn insert<'a>(
+        &mut self,
+        tool: impl Into<String>,
+        hash: u64,
+        artifacts: impl IntoIterator<Item = &'a Artifact>,
+    ) -> Result<(), Error> {
+        let outputs = artifacts
+            .into_iter()
+            .map(|artifact| Ok((artifact.rel_path.clone(), content_hash(&artifact.bytes()?))))
+            .collect::<Result<_, Error>>()?;
+        self.tools.insert(
+            tool.into(),
+            CacheEntry {
+                inputs: hash,
+                outputs,
+            },
+        );
+        Ok(())
+    }
+}
+
+fn content_hash(bytes: &[u8]) -> u64 {
+    let mut hasher = ContentHasher::default();
+    hasher.write(bytes);
+    hasher.finish()
+}
+
+/// 64-bit FNV-1a; unlike `DefaultHasher`, stable across Rust releases so
+/// cached hashes stay valid after a toolchain upgrade.
+pub(crate) struct ContentHasher(u64);
+
+impl Default for ContentHasher {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for ContentHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+/// Delete files under `out_root`, then any directories they leave empty.
+pub fn prune_files(out_root: &Path, paths: &[PathBuf]) -> Result<(), Error> {
+    for path in paths {
+        match fs::remove_file(out_root.join(path)) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        let mut dir = path.parent();
+        while let Some(parent) = dir.filter(|d| !d.as_os_str().is_empty()) {
+            // Fails (and stops) once the directory is not empty
+            if fs::remove_dir(out_root.join(parent)).is_err() {
+                break;
+            }
+            dir = parent.parent();
+        }
+    }
+    Ok(())
+}
+
+/// Compare artifacts with the files under `out_root` without writing anything.
+///
+/// Files under `scan_dirs` (relative to `out_root`; an empty path scans the
+/// whole directory) that no artifact produces are reported as stale.
+pub fn check_artifacts(
+    out_root: &Path,
+    artifacts: &[Artifact],
+    scan_dirs: &[PathBuf],
+) -> Result<Vec<Drift>, Error> {
+    let mut drifts = Vec::new();
+
+    for artifact in artifacts {
+        let path = out_root.join(&artifact.rel_path);
+        let Ok(actual) = fs::read(&path) else {
+            drifts.push(Drift::Missing(artifact.rel_path.clone()));
+            continue;
+        };
+
+        let expected = artifact.bytes()?;
+        if actual != expected {
+            let diff = match (std::str::from_utf8(&actual), std::str::from_utf8(&expected)) {
+                (Ok(actual), Ok(expected)) => {
+                    let name = artifact.rel_path.display().to_string();
+                    TextDiff::from_lines(actual, expected)
+                        .unified_diff()
+                        .header(&format!("a/{name}"), &format!("b/{name}"))
+                        .to_string()
+                }
+                _ => String::new(),
+            };
+            drifts.push(Drift::Changed {
+                path: artifact.rel_path.clone(),
+                diff,
+            });
+        }
+    }
+
+    let produced: HashSet<&Path> = artifacts.iter().map(|a| a.rel_path.as_path()).collect();
+    for dir in scan_dirs {
+        for entry in WalkDir::new(out_root.join(dir))
+            .sort_by_file_name()
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+        {
+            let rel_path = entry
+                .path()
+                .strip_prefix(out_root)
+                .map_err(|_| Error::InvalidPath(entry.path().to_path_buf()))?;
+            let bookkeeping = rel_path == Path::new(MANIFEST) || rel_path == Path::new(CACHE);
+            if !produced.contains(rel_path) && !bookkeeping {
+                drifts.push(Drift::Stale(rel_path.to_path_buf()));
+            }
+        }
+    }
+
+    Ok(drifts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_reports_missing_changed_and_stale() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("helix")).unwrap();
+        fs::write(root.join("helix/same.toml"), "a = 1\n").unwrap();
+        fs::write(root.join("helix/changed.toml"), "a = 1\nb = 2\n").unwrap();
+        fs::write(root.join("helix/old.toml"), "").unwrap();
+
+        let artifacts = vec![
+            Artifact::text("helix/same.toml", "a = 1\n"),
+            Artifact::text("helix/changed.toml", "a = 1\nb = 3\n"),
+            Artifact::text("helix/new.toml", ""),
+        ];
+        let drifts = check_artifacts(root, &artifacts, &[PathBuf::from("helix")]).unwrap();
+
+        assert_eq!(drifts.len(), 3);
+        assert_eq!(drifts[1], Drift::Missing(PathBuf::from("helix/new.toml")));
+        assert_eq!(drifts[2], Drift::Stale(PathBuf::from("helix/old.toml")));
+        let Drift::Changed { path, diff } = &drifts[0] else {
+            panic!("expected Changed, got {:?}", drifts[0]);
+        };
+        assert_eq!(path, Path::new("helix/changed.toml"));
+        assert!(diff.contains("--- a/helix/changed.toml"));
+        assert!(diff.contains("-b = 2\n+b = 3\n"));
+    }
+
+    #[test]
+    fn check_compares_copied_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("src.png");
+        fs::write(&src, [0xFF, 0x00]).unwrap();
+        let out = dir.path().join("out");
+        fs::create_dir_all(out.join("zsh")).unwrap();
+        fs::write(out.join("zsh/icon.png"), [0xFF, 0x01]).unwrap();
+
+        let artifacts = vec![Artifact::copy("zsh/icon.png", &src)];
+        let drifts = check_artifacts(&out, &artifacts, &[]).unwrap();
+        assert_eq!(drifts.len(), 1);
+        assert_eq!(
+            drifts[0].to_string(),
+            "changed: zsh/icon.png (binary files differ)"
+        );
+
+        write_artifacts(&out, &artifacts).unwrap();
+        assert!(
+            check_artifacts(&out, &artifacts, &[PathBuf::new()])
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn manifest_tracks_generated_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+
+        let first = vec![
+            Artifact::text("helix/akari-night.toml", ""),
+            Artifact::text("helix/old/akari-dawn.toml", ""),
+            Artifact::text("zsh/akari.zsh", ""),
+        ];
+        write_artifacts(root, &first).unwrap();
+        fs::write(root.join("helix/README.md"), "hand-written").unwrap();
+        let mut manifest = Manifest::load(root).unwrap();
+        let paths = |artifacts: &[Artifact]| -> Vec<PathBuf> {
+            artifacts.iter().map(|a| a.rel_path.clone()).collect()
+        };
+        assert!(
+            manifest
+                .record(&paths(&first), &[PathBuf::new()])
+                .is_empty()
+        );
+        manifest.save(root).unwrap();
+
+        // Regenerating one tool only considers that tool's files
+        let second = vec![Artifact::text("helix/akari-night.toml", "")];
+        let mut manifest = Manifest::load(root).unwrap();
+        let stale = manifest.record(&paths(&second), &[PathBuf::from("helix")]);
+        assert_eq!(stale, [PathBuf::from("helix/old/akari-dawn.toml")]);
+
+        prune_files(root, &stale).unwrap();
+        manifest.forget(&stale);
+        manifest.save(root).unwrap();
+
+        assert!(!root.join("helix/old").exists());
+        assert!(root.join("helix/README.md").exists());
+        assert!(root.join("zsh/akari.zsh").exists());
+        let content = fs::read_to_string(root.join(MANIFEST)).unwrap();
+        assert!(content.ends_with("helix/akari-night.toml\nzsh/akari.zsh\n"));
+
+        // The manifest itself is never stale
+        let all = [second, vec![Artifact::text("zsh/akari.zsh", "")]].concat();
+        let drifts = check_artifacts(root, &all, &[PathBuf::new()]).unwrap();
+        assert_eq!(drifts, [Drift::Stale(PathBuf::from("helix/README.md"))]);
+    }
+
+    #[test]
+    fn write_skips_identical_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let artifacts = vec![
+            Artifact::text("helix/a.toml", "a = 1\n"),
+            Artifact::text("helix/b.toml", "b = 1\n"),
+        ];
+        assert_eq!(write_artifacts(root, &artifacts).unwrap().len(), 2);
+
+        let modified = fs::metadata(root.join("helix/a.toml"))
+            .unwrap()
+            .modified()
+            .unwrap();
+        let artifacts = vec![
+            Artifact::text("helix/a.toml", "a = 1\n"),
+            Artifact::text("helix/b.toml", "b = 2\n"),
+        ];
+        let written = write_artifacts(root, &artifacts).unwrap();
+        assert_eq!(written, [PathBuf::from("helix/b.toml")]);
+        assert_eq!(
+            fs::metadata(root.join("helix/a.toml"))
+                .unwrap()
+                .modified()
+                .unwrap(),
+            modified
+        );
+    }
+
+    #[test]
+    fn cache_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        assert_eq!(Cache::load(root), Cache::default());
+
+        let artifacts = [
+            Artifact::text("helix/a.toml", "a"),
+            Artifact::text("helix/b c.toml", "b"),
+        ];
+        write_artifacts(root, &artifacts).unwrap();
+        let mut cache = Cache::default();
+        cache.insert("helix", 0xDEAD_BEEF, &artifacts).unwrap();
+        cache.save(root).unwrap();
+
+        let cache = Cache::load(root);
+        assert!(cache.is_fresh(root, "helix", 0xDEAD_BEEF));
+        assert!(!cache.is_fresh(root, "helix", 1));
+        assert!(!cache.is_fresh(root, "zsh", 0xDEAD_BEEF));
+        assert_eq!(
+            cache.outputs("helix").collect::<Vec<_>>(),
+            [Path::new("helix/a.toml"), Path::new("helix/b c.toml")]
+        );
+
+        // The cache is bookkeeping, never stale output
+        assert!(
+            check_artifacts(root, &artifacts, &[PathBuf::new()])
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn cache_is_stale_when_outputs_are_edited() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let artifacts = [
+            Artifact::text("helix/a.toml", "a"),
+            Artifact::text("helix/b.toml", "b"),
+        ];
+        write_artifacts(root, &artifacts).unwrap();
+        let mut cache = Cache::default();
+        cache.insert("helix", 1, &artifacts).unwrap();
+        assert!(cache.is_fresh(root, "helix", 1));
+
+        fs::write(root.join("helix/a.toml"), "edited").unwrap();
+        assert!(!cache.is_fresh(root, "helix", 1));
+
+        fs::write(root.join("helix/a.toml"), "a").unwrap();
+        fs::remove_file(root.join("helix/b.toml")).unwrap();
+        assert!(!cache.is_fresh(root, "helix", 1));
+    }
+
+    #[test]
+    fn content_hasher_is_fnv1a() {
+        let mut hasher = ContentHasher::default();
+        hasher.write(b"a");
+        assert_eq!(hasher.finish(), 0xaf63_dc4c_8601_ec8c);
+    }
+}
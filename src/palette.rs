@@ -1,606 +1,604 @@
-use crate::{Error, Rgb, Variant};
+use crate::expr::{ColorExpr, Section, is_ident, parse_color_expr};
+use crate::{Error, Rgba, Variant};
 use serde::{Deserialize, Serialize};
+use std::array;
 use std::collections::BTreeMap;
 use std::fs;
-use std::path::Path;
+use std::iter::Zip;
+use std::path::{Path, PathBuf};
+use toml::{Table, Value};
 
-// Raw types for TOML deserialization (before reference resolution)
+/// Palette file layout.
+///
+/// Deserialized from the table after every color expression has been resolved
+/// to hex, so the section structs double as the resolved types.
 #[derive(Debug, Deserialize)]
 struct RawPalette {
     name: String,
     description: String,
     colors: Colors,
     base: Base,
-    layers: RawLayers,
-    state: RawState,
-    semantic: RawSemantic,
+    layers: Layers,
+    state: State,
+    semantic: Semantic,
     ansi: RawAnsi,
 }
 
-#[derive(Debug, Deserialize)]
-struct RawLayers {
-    base: ColorExpr,
-    surface: ColorExpr,
-    sunken: ColorExpr,
-    raised: ColorExpr,
-    border: ColorExpr,
-    inset: ColorExpr,
-}
-
-impl RawLayers {
-    fn resolve(&self, resolver: &Resolver) -> Result<Layers, Error> {
-        Ok(Layers {
-            base: resolve_expr(resolver, &self.base)?,
-            surface: resolve_expr(resolver, &self.surface)?,
-            sunken: resolve_expr(resolver, &self.sunken)?,
-            raised: resolve_expr(resolver, &self.raised)?,
-            border: resolve_expr(resolver, &self.border)?,
-            inset: resolve_expr(resolver, &self.inset)?,
-        })
-    }
-}
-
-#[derive(Debug, Deserialize)]
-struct RawState {
-    selection_bg: ColorExpr,
-    selection_fg: ColorExpr,
-    match_bg: ColorExpr,
-    cursor: ColorExpr,
-    cursor_text: ColorExpr,
-    info: ColorExpr,
-    hint: ColorExpr,
-    warning: ColorExpr,
-    error: ColorExpr,
-    active_bg: ColorExpr,
-    diff_added: ColorExpr,
-    diff_added_bg: ColorExpr,
-    diff_removed: ColorExpr,
-    diff_removed_bg: ColorExpr,
-    diff_changed: ColorExpr,
-    diff_moved: ColorExpr,
-    conflict: ColorExpr,
-}
-
-impl RawState {
-    fn resolve(&self, resolver: &Resolver) -> Result<State, Error> {
-        Ok(State {
-            selection_bg: resolve_expr(resolver, &self.selection_bg)?,
-            selection_fg: resolve_expr(resolver, &self.selection_fg)?,
-            match_bg: resolve_expr(resolver, &self.match_bg)?,
-            cursor: resolve_expr(resolver, &self.cursor)?,
-            cursor_text: resolve_expr(resolver, &self.cursor_text)?,
-            info: resolve_expr(resolver, &self.info)?,
-            hint: resolve_expr(resolver, &self.hint)?,
-            warning: resolve_expr(resolver, &self.warning)?,
-            error: resolve_expr(resolver, &self.error)?,
-            active_bg: resolve_expr(resolver, &self.active_bg)?,
-            diff_added: resolve_expr(resolver, &self.diff_added)?,
-            diff_added_bg: resolve_expr(resolver, &self.diff_added_bg)?,
-            diff_removed: resolve_expr(resolver, &self.diff_removed)?,
-            diff_removed_bg: resolve_expr(resolver, &self.diff_removed_bg)?,
-            diff_changed: resolve_expr(resolver, &self.diff_changed)?,
-            diff_moved: resolve_expr(resolver, &self.diff_moved)?,
-            conflict: resolve_expr(resolver, &self.conflict)?,
-        })
-    }
-}
-
-/// Common structure for ANSI color definitions (used by both ansi and ansi.bright)
-#[derive(Debug, Deserialize)]
-struct RawAnsiColors {
-    black: ColorExpr,
-    red: ColorExpr,
-    green: ColorExpr,
-    yellow: ColorExpr,
-    blue: ColorExpr,
-    magenta: ColorExpr,
-    cyan: ColorExpr,
-    white: ColorExpr,
-}
-
-impl RawAnsiColors {
-    fn resolve(&self, resolver: &impl ResolveRef) -> Result<Ansi, Error> {
-        Ok(Ansi {
-            black: resolve_expr(resolver, &self.black)?,
-            red: resolve_expr(resolver, &self.red)?,
-            green: resolve_expr(resolver, &self.green)?,
-            yellow: resolve_expr(resolver, &self.yellow)?,
-            blue: resolve_expr(resolver, &self.blue)?,
-            magenta: resolve_expr(resolver, &self.magenta)?,
-            cyan: resolve_expr(resolver, &self.cyan)?,
-            white: resolve_expr(resolver, &self.white)?,
-        })
-    }
-}
-
 #[derive(Debug, Deserialize)]
 struct RawAnsi {
     #[serde(flatten)]
-    base: RawAnsiColors,
-    bright: RawAnsiColors,
-}
-
-#[derive(Debug, Deserialize)]
-struct RawSemantic {
-    text: ColorExpr,
-    comment: ColorExpr,
-    string: ColorExpr,
-    keyword: ColorExpr,
-    number: ColorExpr,
-    constant: ColorExpr,
-    r#type: ColorExpr,
-    function: ColorExpr,
-    variable: ColorExpr,
-    success: ColorExpr,
-    path: ColorExpr,
-    r#macro: ColorExpr,
-    escape: ColorExpr,
-    regexp: ColorExpr,
-    link: ColorExpr,
-    directory: ColorExpr,
-}
-
-impl RawSemantic {
-    fn resolve(&self, resolver: &Resolver) -> Result<Semantic, Error> {
-        Ok(Semantic {
-            text: resolve_expr(resolver, &self.text)?,
-            comment: resolve_expr(resolver, &self.comment)?,
-            string: resolve_expr(resolver, &self.string)?,
-            keyword: resolve_expr(resolver, &self.keyword)?,
-            number: resolve_expr(resolver, &self.number)?,
-            constant: resolve_expr(resolver, &self.constant)?,
-            r#type: resolve_expr(resolver, &self.r#type)?,
-            function: resolve_expr(resolver, &self.function)?,
-            variable: resolve_expr(resolver, &self.variable)?,
-            success: resolve_expr(resolver, &self.success)?,
-            path: resolve_expr(resolver, &self.path)?,
-            r#macro: resolve_expr(resolver, &self.r#macro)?,
-            escape: resolve_expr(resolver, &self.escape)?,
-            regexp: resolve_expr(resolver, &self.regexp)?,
-            link: resolve_expr(resolver, &self.link)?,
-            directory: resolve_expr(resolver, &self.directory)?,
-        })
-    }
+    base: Ansi,
+    bright: Ansi,
 }
 
 // Resolved types (used for both deserialization and template rendering)
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Lantern {
-    pub ember: String, // inner heat — flame, fuel, origin of light
-    pub near: String,  // hibukuro — paper seen up close
-    pub mid: String,   // glow — lantern as perceived light
-    pub far: String,   // warm blur — light at a distance
+    pub ember: Rgba, // inner heat — flame, fuel, origin of light
+    pub near: Rgba,  // hibukuro — paper seen up close
+    pub mid: Rgba,   // glow — lantern as perceived light
+    pub far: Rgba,   // warm blur — light at a distance
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Colors {
     pub lantern: Lantern,
-    pub life: String,
-    pub night: String,
-    pub rain: String,
-    pub muted: String,
+    pub life: Rgba,
+    pub night: Rgba,
+    pub rain: Rgba,
+    pub muted: Rgba,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Base {
-    pub background: String,
-    pub foreground: String,
+    pub background: Rgba,
+    pub foreground: Rgba,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Layers {
-    pub base: String,
-    pub surface: String,
-    pub sunken: String,
-    pub raised: String,
-    pub border: String,
-    pub inset: String,
+    pub base: Rgba,
+    pub surface: Rgba,
+    pub sunken: Rgba,
+    pub raised: Rgba,
+    pub border: Rgba,
+    pub inset: Rgba,
+    /// Surfaces defined by the palette file beyond the built-in ones (e.g. `layers.overlay`)
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, Rgba>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct State {
-    pub selection_bg: String,
-    pub selection_fg: String,
-    pub match_bg: String,
-    pub cursor: String,
-    pub cursor_text: String,
-    pub info: String,
-    pub hint: String,
-    pub warning: String,
-    pub error: String,
-    pub active_bg: String,
-    pub diff_added: String,
-    pub diff_added_bg: String,
-    pub diff_removed: String,
-    pub diff_removed_bg: String,
-    pub diff_changed: String,
-    pub diff_moved: String,
-    pub conflict: String,
-}
-
-#[derive(Debug, Clone, Serialize)]
+    pub selection_bg: Rgba,
+    pub selection_fg: Rgba,
+    pub match_bg: Rgba,
+    pub cursor: Rgba,
+    pub cursor_text: Rgba,
+    pub info: Rgba,
+    pub hint: Rgba,
+    pub warning: Rgba,
+    pub error: Rgba,
+    pub active_bg: Rgba,
+    pub diff_added: Rgba,
+    pub diff_added_bg: Rgba,
+    pub diff_removed: Rgba,
+    pub diff_removed_bg: Rgba,
+    pub diff_changed: Rgba,
+    pub diff_moved: Rgba,
+    pub conflict: Rgba,
+    /// UI states defined by the palette file beyond the built-in ones (e.g. `state.diff_text_bg`)
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, Rgba>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Semantic {
-    pub text: String,
-    pub comment: String,
-    pub string: String,
-    pub keyword: String,
-    pub number: String,
-    pub constant: String,
-    pub r#type: String,
-    pub function: String,
-    pub variable: String,
-    pub success: String,
-    pub path: String,
-    pub r#macro: String,
-    pub escape: String,
-    pub regexp: String,
-    pub link: String,
-    pub directory: String,
-}
-
-#[derive(Debug, Clone, Serialize)]
-pub struct Ansi {
-    pub black: String,
-    pub red: String,
-    pub green: String,
-    pub yellow: String,
-    pub blue: String,
-    pub magenta: String,
-    pub cyan: String,
-    pub white: String,
+    pub text: Rgba,
+    pub comment: Rgba,
+    pub string: Rgba,
+    pub keyword: Rgba,
+    pub number: Rgba,
+    pub constant: Rgba,
+    pub r#type: Rgba,
+    pub function: Rgba,
+    pub variable: Rgba,
+    pub success: Rgba,
+    pub path: Rgba,
+    pub r#macro: Rgba,
+    pub escape: Rgba,
+    pub regexp: Rgba,
+    pub link: Rgba,
+    pub directory: Rgba,
+    /// Syntax roles defined by the palette file beyond the built-in ones (e.g. `semantic.operator`)
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, Rgba>,
 }
 
-impl Ansi {
-    /// Convert to a map for use in Resolver
-    fn to_map(&self) -> BTreeMap<&'static str, String> {
-        self.into_iter().map(|(k, v)| (k, v.to_string())).collect()
+impl Colors {
+    /// Keys in this section, in iteration order
+    pub const KEYS: [&'static str; 8] = [
+        "lantern.ember",
+        "lantern.near",
+        "lantern.mid",
+        "lantern.far",
+        "life",
+        "night",
+        "rain",
+        "muted",
+    ];
+
+    /// Values in `KEYS` order.
+    fn values(&self) -> [Rgba; 8] {
+        [
+            self.lantern.ember,
+            self.lantern.near,
+            self.lantern.mid,
+            self.lantern.far,
+            self.life,
+            self.night,
+            self.rain,
+            self.muted,
+        ]
+    }
+
+    /// Color for `key` (e.g. `"lantern.mid"`).
+    pub(crate) fn get(&self, key: &str) -> Option<Rgba> {
+        Self::KEYS
+            .iter()
+            .position(|k| *k == key)
+            .map(|i| self.values()[i])
     }
 }
 
-impl<'a> IntoIterator for &'a Ansi {
-    type Item = (&'static str, &'a str);
-    type IntoIter = std::array::IntoIter<Self::Item, 8>;
+impl IntoIterator for &Colors {
+    type Item = (&'static str, Rgba);
+    type IntoIter = Zip<array::IntoIter<&'static str, 8>, array::IntoIter<Rgba, 8>>;
 
     fn into_iter(self) -> Self::IntoIter {
+        Colors::KEYS.into_iter().zip(self.values())
+    }
+}
+
+impl Base {
+    /// Keys in this section, in iteration order
+    pub const KEYS: [&'static str; 2] = ["background", "foreground"];
+
+    /// Values in `KEYS` order.
+    fn values(&self) -> [Rgba; 2] {
+        [self.background, self.foreground]
+    }
+
+    /// Color for `key`.
+    pub(crate) fn get(&self, key: &str) -> Option<Rgba> {
+        Self::KEYS
+            .iter()
+            .position(|k| *k == key)
+            .map(|i| self.values()[i])
+    }
+}
+
+impl IntoIterator for &Base {
+    type Item = (&'static str, Rgba);
+    type IntoIter = Zip<array::IntoIter<&'static str, 2>, array::IntoIter<Rgba, 2>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Base::KEYS.into_iter().zip(self.values())
+    }
+}
+
+impl Layers {
+    /// Built-in keys in this section, in iteration order (before `extra`)
+    pub const KEYS: [&'static str; 6] = ["base", "surface", "sunken", "raised", "border", "inset"];
+
+    /// Values in `KEYS` order.
+    fn values(&self) -> [Rgba; 6] {
         [
-            ("black", self.black.as_str()),
-            ("red", self.red.as_str()),
-            ("green", self.green.as_str()),
-            ("yellow", self.yellow.as_str()),
-            ("blue", self.blue.as_str()),
-            ("magenta", self.magenta.as_str()),
-            ("cyan", self.cyan.as_str()),
-            ("white", self.white.as_str()),
+            self.base,
+            self.surface,
+            self.sunken,
+            self.raised,
+            self.border,
+            self.inset,
         ]
-        .into_iter()
+    }
+
+    /// Color for `key`, built-in or extra.
+    pub(crate) fn get(&self, key: &str) -> Option<Rgba> {
+        Self::KEYS
+            .iter()
+            .position(|k| *k == key)
+            .map(|i| self.values()[i])
+            .or_else(|| self.extra.get(key).copied())
     }
 }
 
-/// Sections that can be referenced in color expressions.
-///
-/// Only `colors`, `base`, and `ansi` are valid reference targets.
-/// `ansi.bright.*` is accessed via `Section::Ansi` with key `"bright.*"`.
-/// Other sections like `layers`, `state`, and `semantic` are consumers of colors,
-/// not sources, and cannot be referenced.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Section {
-    Colors,
-    Base,
-    Ansi,
-}
-
-impl Section {
-    /// Referenceable sections in color expressions.
-    const ALLOWED: &[&str] = &["colors", "base", "ansi"];
-
-    fn parse(s: &str) -> Result<Self, Error> {
-        match s {
-            "colors" => Ok(Self::Colors),
-            "base" => Ok(Self::Base),
-            "ansi" => Ok(Self::Ansi),
-            _ => Err(Error::InvalidColorExpr(format!(
-                "'{s}' cannot be referenced (allowed: {})",
-                Self::ALLOWED.join(", ")
-            ))),
-        }
+impl<'a> IntoIterator for &'a Layers {
+    type Item = (&'a str, Rgba);
+    type IntoIter = std::vec::IntoIter<Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let mut entries: Vec<_> = Layers::KEYS.into_iter().zip(self.values()).collect();
+        entries.extend(self.extra.iter().map(|(key, color)| (key.as_str(), *color)));
+        entries.into_iter()
     }
+}
 
-    const fn as_str(&self) -> &'static str {
-        match self {
-            Self::Colors => "colors",
-            Self::Base => "base",
-            Self::Ansi => "ansi",
-        }
+impl State {
+    /// Built-in keys in this section, in iteration order (before `extra`)
+    pub const KEYS: [&'static str; 17] = [
+        "selection_bg",
+        "selection_fg",
+        "match_bg",
+        "cursor",
+        "cursor_text",
+        "info",
+        "hint",
+        "warning",
+        "error",
+        "active_bg",
+        "diff_added",
+        "diff_added_bg",
+        "diff_removed",
+        "diff_removed_bg",
+        "diff_changed",
+        "diff_moved",
+        "conflict",
+    ];
+
+    /// Values in `KEYS` order.
+    fn values(&self) -> [Rgba; 17] {
+        [
+            self.selection_bg,
+            self.selection_fg,
+            self.match_bg,
+            self.cursor,
+            self.cursor_text,
+            self.info,
+            self.hint,
+            self.warning,
+            self.error,
+            self.active_bg,
+            self.diff_added,
+            self.diff_added_bg,
+            self.diff_removed,
+            self.diff_removed_bg,
+            self.diff_changed,
+            self.diff_moved,
+            self.conflict,
+        ]
+    }
+
+    /// Color for `key`, built-in or extra.
+    pub(crate) fn get(&self, key: &str) -> Option<Rgba> {
+        Self::KEYS
+            .iter()
+            .position(|k| *k == key)
+            .map(|i| self.values()[i])
+            .or_else(|| self.extra.get(key).copied())
     }
 }
 
-/// A color expression that can be deserialized from TOML.
-///
-/// Supports:
-/// - Literal hex colors: `"#E26A3B"`
-/// - References: `"colors.lantern"`
-/// - Functions:
-///   - `"lighten(colors.lantern, 0.1)"` — increase lightness proportionally
-///   - `"darken(base.background, 0.2)"` — decrease lightness proportionally
-///   - `"brighten(ansi.red, 0.1)"` — adjust lightness by absolute amount
-///   - `"mix(base.background, colors.night, 0.15)"` — blend two colors
-#[derive(Debug, Clone, Deserialize)]
-#[serde(try_from = "String")]
-enum ColorExpr {
-    /// A literal hex color (e.g., "#E26A3B")
-    Literal(String),
-    /// A reference to another field (e.g., "colors.lantern")
-    Ref { section: Section, key: String },
-    /// Lighten a color by a factor (0.0 = unchanged, 1.0 = white)
-    Lighten(Box<ColorExpr>, f64),
-    /// Darken a color by a factor (0.0 = unchanged, 1.0 = black)
-    Darken(Box<ColorExpr>, f64),
-    /// Brighten a color by absolute amount (positive = brighter, negative = dimmer)
-    Brighten(Box<ColorExpr>, f64),
-    /// Mix two colors (0.0 = first color, 1.0 = second color)
-    Mix(Box<ColorExpr>, Box<ColorExpr>, f64),
-}
-
-impl TryFrom<String> for ColorExpr {
-    type Error = Error;
-
-    fn try_from(s: String) -> Result<Self, Self::Error> {
-        parse_color_expr(&s)
-    }
-}
-
-/// Strip function call syntax: "fn_name(args)" -> Some("args")
-fn strip_fn_call<'a>(s: &'a str, name: &str) -> Option<&'a str> {
-    s.strip_prefix(name)
-        .and_then(|r| r.strip_prefix('('))
-        .and_then(|r| r.strip_suffix(')'))
-}
-
-/// Parse a color expression string into a ColorExpr.
-fn parse_color_expr(s: &str) -> Result<ColorExpr, Error> {
-    let s = s.trim();
-
-    // Literal hex color
-    if s.starts_with('#') {
-        return Ok(ColorExpr::Literal(s.to_string()));
-    }
-
-    // Function call: lighten(...), darken(...), brighten(...), mix(...)
-    if let Some(args) = strip_fn_call(s, "lighten") {
-        let (inner, factor) = parse_unary_fn_args(args)?;
-        return Ok(ColorExpr::Lighten(Box::new(inner), factor));
-    }
-    if let Some(args) = strip_fn_call(s, "darken") {
-        let (inner, factor) = parse_unary_fn_args(args)?;
-        return Ok(ColorExpr::Darken(Box::new(inner), factor));
-    }
-    if let Some(args) = strip_fn_call(s, "brighten") {
-        let (inner, amount) = parse_unary_fn_args(args)?;
-        return Ok(ColorExpr::Brighten(Box::new(inner), amount));
-    }
-    if let Some(args) = strip_fn_call(s, "mix") {
-        let (color1, color2, factor) = parse_mix_args(args)?;
-        return Ok(ColorExpr::Mix(Box::new(color1), Box::new(color2), factor));
-    }
-
-    // Reference: section.key (e.g., "colors.lantern.mid", "ansi.bright.red")
-    let (section_str, key) = s
-        .split_once('.')
-        .ok_or_else(|| Error::InvalidColorExpr(s.to_string()))?;
-    let section = Section::parse(section_str)?;
-    Ok(ColorExpr::Ref {
-        section,
-        key: key.to_string(),
-    })
-}
-
-/// Parse unary function arguments: "colors.lantern, 0.1" -> (ColorExpr, f64)
-fn parse_unary_fn_args(args: &str) -> Result<(ColorExpr, f64), Error> {
-    let (color_str, factor_str) = args
-        .rsplit_once(',')
-        .ok_or_else(|| Error::InvalidColorExpr(format!("expected 'color, factor': {args}")))?;
-    let inner = parse_color_expr(color_str.trim())?;
-    let factor = factor_str
-        .trim()
-        .parse::<f64>()
-        .map_err(|_| Error::InvalidColorExpr(format!("invalid factor: {}", factor_str.trim())))?;
-    Ok((inner, factor))
-}
-
-/// Parse mix function arguments: "color1, color2, 0.15" -> (ColorExpr, ColorExpr, f64)
-fn parse_mix_args(args: &str) -> Result<(ColorExpr, ColorExpr, f64), Error> {
-    // Split from right to get factor first
-    let (rest, factor_str) = args.rsplit_once(',').ok_or_else(|| {
-        Error::InvalidColorExpr(format!("expected 'color1, color2, factor': {args}"))
-    })?;
-    let factor = factor_str
-        .trim()
-        .parse::<f64>()
-        .map_err(|_| Error::InvalidColorExpr(format!("invalid factor: {}", factor_str.trim())))?;
-
-    // Split remaining to get two colors
-    let (color1_str, color2_str) = rest.rsplit_once(',').ok_or_else(|| {
-        Error::InvalidColorExpr(format!("expected 'color1, color2, factor': {args}"))
-    })?;
-    let color1 = parse_color_expr(color1_str.trim())?;
-    let color2 = parse_color_expr(color2_str.trim())?;
-
-    Ok((color1, color2, factor))
+impl<'a> IntoIterator for &'a State {
+    type Item = (&'a str, Rgba);
+    type IntoIter = std::vec::IntoIter<Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let mut entries: Vec<_> = State::KEYS.into_iter().zip(self.values()).collect();
+        entries.extend(self.extra.iter().map(|(key, color)| (key.as_str(), *color)));
+        entries.into_iter()
+    }
+}
+
+impl Semantic {
+    /// Built-in keys in this section, in iteration order (before `extra`)
+    pub const KEYS: [&'static str; 16] = [
+        "text",
+        "comment",
+        "string",
+        "keyword",
+        "number",
+        "constant",
+        "type",
+        "function",
+        "variable",
+        "success",
+        "path",
+        "macro",
+        "escape",
+        "regexp",
+        "link",
+        "directory",
+    ];
+
+    /// Values in `KEYS` order.
+    fn values(&self) -> [Rgba; 16] {
+        [
+            self.text,
+            self.comment,
+            self.string,
+            self.keyword,
+            self.number,
+            self.constant,
+            self.r#type,
+            self.function,
+            self.variable,
+            self.success,
+            self.path,
+            self.r#macro,
+            self.escape,
+            self.regexp,
+            self.link,
+            self.directory,
+        ]
+    }
+
+    /// Color for `key`, built-in or extra.
+    pub(crate) fn get(&self, key: &str) -> Option<Rgba> {
+        Self::KEYS
+            .iter()
+            .position(|k| *k == key)
+            .map(|i| self.values()[i])
+            .or_else(|| self.extra.get(key).copied())
+    }
+}
+
+impl<'a> IntoIterator for &'a Semantic {
+    type Item = (&'a str, Rgba);
+    type IntoIter = std::vec::IntoIter<Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let mut entries: Vec<_> = Semantic::KEYS.into_iter().zip(self.values()).collect();
+        entries.extend(self.extra.iter().map(|(key, color)| (key.as_str(), *color)));
+        entries.into_iter()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Ansi {
+    pub black: Rgba,
+    pub red: Rgba,
+    pub green: Rgba,
+    pub yellow: Rgba,
+    pub blue: Rgba,
+    pub magenta: Rgba,
+    pub cyan: Rgba,
+    pub white: Rgba,
+}
+
+impl Ansi {
+    /// Keys in this section, in iteration order
+    pub const KEYS: [&'static str; 8] = [
+        "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+    ];
+
+    /// Values in `KEYS` order.
+    fn values(&self) -> [Rgba; 8] {
+        [
+            self.black,
+            self.red,
+            self.green,
+            self.yellow,
+            self.blue,
+            self.magenta,
+            self.cyan,
+            self.white,
+        ]
+    }
+
+    /// Color for `key`.
+    pub(crate) fn get(&self, key: &str) -> Option<Rgba> {
+        Self::KEYS
+            .iter()
+            .position(|k| *k == key)
+            .map(|i| self.values()[i])
+    }
+}
+
+impl IntoIterator for &Ansi {
+    type Item = (&'static str, Rgba);
+    type IntoIter = Zip<array::IntoIter<&'static str, 8>, array::IntoIter<Rgba, 8>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Ansi::KEYS.into_iter().zip(self.values())
+    }
 }
 
 /// Trait for resolving color references.
-trait ResolveRef {
-    fn resolve_ref(&self, section: Section, key: &str) -> Result<String, Error>;
+pub(crate) trait ResolveRef {
+    fn resolve_ref(&self, section: Section, key: &str) -> Result<Rgba, Error>;
 }
 
-/// Resolve a color expression using a resolver.
-fn resolve_expr(resolver: &impl ResolveRef, expr: &ColorExpr) -> Result<String, Error> {
+/// Colors resolved so far, keyed by full path (e.g. "ansi.bright.red").
+impl ResolveRef for BTreeMap<String, Rgba> {
+    fn resolve_ref(&self, section: Section, key: &str) -> Result<Rgba, Error> {
+        let path = format!("{}.{key}", section.as_str());
+        self.get(&path).copied().ok_or(Error::UnresolvedRef(path))
+    }
+}
+
+/// Evaluate a color expression to a color, carrying alpha through every step.
+pub(crate) fn eval_expr(resolver: &impl ResolveRef, expr: &ColorExpr) -> Result<Rgba, Error> {
     match expr {
-        ColorExpr::Literal(hex) => Ok(hex.clone()),
+        ColorExpr::Literal(hex) => hex.parse(),
         ColorExpr::Ref { section, key } => resolver.resolve_ref(*section, key),
         ColorExpr::Lighten(inner, factor) => {
-            let hex = resolve_expr(resolver, inner)?;
-            Ok(hex.parse::<Rgb>()?.lighten(*factor).to_string())
+            Ok(eval_expr(resolver, inner)?.map_rgb(|c| c.lighten(*factor)))
         }
         ColorExpr::Darken(inner, factor) => {
-            let hex = resolve_expr(resolver, inner)?;
-            Ok(hex.parse::<Rgb>()?.darken(*factor).to_string())
+            Ok(eval_expr(resolver, inner)?.map_rgb(|c| c.darken(*factor)))
         }
         ColorExpr::Brighten(inner, amount) => {
-            let hex = resolve_expr(resolver, inner)?;
-            Ok(hex.parse::<Rgb>()?.brighten(*amount).to_string())
+            Ok(eval_expr(resolver, inner)?.map_rgb(|c| c.brighten(*amount)))
         }
         ColorExpr::Mix(color1, color2, factor) => {
-            let rgb1: Rgb = resolve_expr(resolver, color1)?.parse()?;
-            let rgb2: Rgb = resolve_expr(resolver, color2)?.parse()?;
-            Ok(rgb1.mix(rgb2, *factor).to_string())
+            let rgba1 = eval_expr(resolver, color1)?;
+            let rgba2 = eval_expr(resolver, color2)?;
+            Ok(rgba1.mix(rgba2, *factor))
+        }
+        ColorExpr::Alpha(inner, alpha) => Ok(eval_expr(resolver, inner)?.with_alpha(*alpha)),
+        ColorExpr::OklchLighten(inner, factor) => {
+            Ok(eval_expr(resolver, inner)?.map_rgb(|c| c.oklch_lighten(*factor)))
+        }
+        ColorExpr::OklchDarken(inner, factor) => {
+            Ok(eval_expr(resolver, inner)?.map_rgb(|c| c.oklch_darken(*factor)))
+        }
+        ColorExpr::OklchMix(color1, color2, factor) => {
+            let rgba1 = eval_expr(resolver, color1)?;
+            let rgba2 = eval_expr(resolver, color2)?;
+            Ok(rgba1.oklch_mix(rgba2, *factor))
+        }
+        ColorExpr::Chroma(inner, factor) => {
+            Ok(eval_expr(resolver, inner)?.map_rgb(|c| c.chroma(*factor)))
+        }
+        ColorExpr::EnsureContrast(fg, bg, ratio) => {
+            let bg = eval_expr(resolver, bg)?.rgb();
+            Ok(eval_expr(resolver, fg)?.map_rgb(|c| c.ensure_contrast(bg, *ratio)))
         }
     }
 }
 
-struct Resolver<'a> {
-    colors: BTreeMap<&'a str, &'a str>,
-    base: BTreeMap<&'a str, &'a str>,
-    /// Resolved hex values for ansi (includes both ansi.* and ansi.bright.*)
-    ansi_map: BTreeMap<String, String>,
-    /// Resolved ansi colors (to avoid re-resolving)
-    resolved_ansi: Ansi,
-    /// Resolved ansi.bright colors (to avoid re-resolving)
-    resolved_ansi_bright: Ansi,
-}
-
-impl<'a> Resolver<'a> {
-    fn new(raw: &'a RawPalette) -> Result<Self, Error> {
-        // Flatten nested lantern structure into colors map
-        let colors: BTreeMap<&str, &str> = [
-            ("lantern.ember", raw.colors.lantern.ember.as_str()),
-            ("lantern.near", raw.colors.lantern.near.as_str()),
-            ("lantern.mid", raw.colors.lantern.mid.as_str()),
-            ("lantern.far", raw.colors.lantern.far.as_str()),
-            ("life", raw.colors.life.as_str()),
-            ("night", raw.colors.night.as_str()),
-            ("rain", raw.colors.rain.as_str()),
-            ("muted", raw.colors.muted.as_str()),
-        ]
-        .into_iter()
-        .collect();
-        let base: BTreeMap<&str, &str> = [
-            ("background", raw.base.background.as_str()),
-            ("foreground", raw.base.foreground.as_str()),
-        ]
-        .into_iter()
-        .collect();
-
-        // Resolve ansi first (it only depends on colors/base)
-        let partial = PartialResolver {
-            colors: &colors,
-            base: &base,
-            ansi: None,
-        };
-        let resolved_ansi = raw.ansi.base.resolve(&partial)?;
-
-        // Build ansi_map with keys like "red", "green", etc.
-        let mut ansi_map: BTreeMap<String, String> = resolved_ansi
-            .to_map()
-            .into_iter()
-            .map(|(k, v)| (k.to_string(), v))
-            .collect();
-
-        // Resolve ansi.bright (depends on ansi)
-        let partial_with_ansi = PartialResolver {
-            colors: &colors,
-            base: &base,
-            ansi: Some(&ansi_map),
-        };
-        let resolved_ansi_bright = raw.ansi.bright.resolve(&partial_with_ansi)?;
+/// Recursively merge `overlay` into `base`; overlay values win, nested tables merge.
+fn merge_tables(base: &mut Table, overlay: Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(Value::Table(base_nested)), Value::Table(nested)) => {
+                merge_tables(base_nested, nested);
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
 
-        // Add ansi.bright.* to ansi_map with keys like "bright.red", "bright.green", etc.
-        for (k, v) in resolved_ansi_bright.to_map() {
-            ansi_map.insert(format!("bright.{k}"), v);
+/// Parse every color expression under the palette sections, keyed by path.
+fn collect_exprs(table: &Table) -> Result<BTreeMap<String, ColorExpr>, Error> {
+    fn walk(
+        prefix: &str,
+        table: &Table,
+        exprs: &mut BTreeMap<String, ColorExpr>,
+    ) -> Result<(), Error> {
+        for (key, value) in table {
+            let path = format!("{prefix}.{key}");
+            match value {
+                Value::String(src) => {
+                    let expr = parse_color_expr(src).map_err(|e| e.with_key(&path))?;
+                    exprs.insert(path, expr);
+                }
+                Value::Table(nested) => walk(&path, nested, exprs)?,
+                // Left for deserialization to report as a type error
+                _ => {}
+            }
         }
+        Ok(())
+    }
 
-        Ok(Self {
-            colors,
-            base,
-            ansi_map,
-            resolved_ansi,
-            resolved_ansi_bright,
-        })
+    let mut exprs = BTreeMap::new();
+    for section in Section::ALL {
+        if let Some(Value::Table(t)) = table.get(section.as_str()) {
+            walk(section.as_str(), t, &mut exprs)?;
+        }
     }
+    Ok(exprs)
 }
 
-impl ResolveRef for Resolver<'_> {
-    fn resolve_ref(&self, section: Section, key: &str) -> Result<String, Error> {
-        let ref_str = || format!("{}.{key}", section.as_str());
-        match section {
-            Section::Colors => self
-                .colors
-                .get(key)
-                .copied()
-                .map(str::to_string)
-                .ok_or_else(|| Error::UnresolvedRef(ref_str())),
-            Section::Base => self
-                .base
-                .get(key)
-                .copied()
-                .map(str::to_string)
-                .ok_or_else(|| Error::UnresolvedRef(ref_str())),
-            Section::Ansi => self
-                .ansi_map
-                .get(key)
-                .cloned()
-                .ok_or_else(|| Error::UnresolvedRef(ref_str())),
+/// Order paths so that every expression comes after the ones it references.
+///
+/// Fails with [`Error::CyclicRef`] naming the full cycle if references loop.
+fn topological_order(exprs: &BTreeMap<String, ColorExpr>) -> Result<Vec<&str>, Error> {
+    #[derive(Clone, Copy, PartialEq, Eq)]
+    enum Mark {
+        Visiting,
+        Done,
+    }
+
+    fn visit<'a>(
+        path: &str,
+        exprs: &'a BTreeMap<String, ColorExpr>,
+        marks: &mut BTreeMap<&'a str, Mark>,
+        stack: &mut Vec<&'a str>,
+        order: &mut Vec<&'a str>,
+    ) -> Result<(), Error> {
+        match marks.get(path) {
+            Some(Mark::Done) => return Ok(()),
+            Some(Mark::Visiting) => {
+                let start = stack.iter().position(|p| *p == path).unwrap_or(0);
+                let mut cycle: Vec<String> = stack[start..].iter().map(|p| p.to_string()).collect();
+                cycle.push(path.to_string());
+                return Err(Error::CyclicRef(cycle));
+            }
+            None => {}
+        }
+
+        let (path, expr) = exprs
+            .get_key_value(path)
+            .ok_or_else(|| Error::UnresolvedRef(path.to_string()))?;
+
+        marks.insert(path, Mark::Visiting);
+        stack.push(path);
+        for dep in expr.refs() {
+            visit(&dep, exprs, marks, stack, order)?;
         }
+        stack.pop();
+        marks.insert(path, Mark::Done);
+        order.push(path);
+
+        Ok(())
     }
-}
 
-/// Resolver for bootstrapping ansi/ansi.bright resolution.
-/// Only colors, base, and optionally ansi are available.
-struct PartialResolver<'a> {
-    colors: &'a BTreeMap<&'a str, &'a str>,
-    base: &'a BTreeMap<&'a str, &'a str>,
-    ansi: Option<&'a BTreeMap<String, String>>,
+    let mut marks = BTreeMap::new();
+    let mut stack = Vec::new();
+    let mut order = Vec::with_capacity(exprs.len());
+    for path in exprs.keys() {
+        visit(path, exprs, &mut marks, &mut stack, &mut order)?;
+    }
+    Ok(order)
 }
 
-impl ResolveRef for PartialResolver<'_> {
-    fn resolve_ref(&self, section: Section, key: &str) -> Result<String, Error> {
-        let ref_str = || format!("{}.{key}", section.as_str());
-        match section {
-            Section::Colors => self
-                .colors
-                .get(key)
-                .copied()
-                .map(str::to_string)
-                .ok_or_else(|| Error::UnresolvedRef(ref_str())),
-            Section::Base => self
-                .base
-                .get(key)
-                .copied()
-                .map(str::to_string)
-                .ok_or_else(|| Error::UnresolvedRef(ref_str())),
-            Section::Ansi => self
-                .ansi
-                .and_then(|m| m.get(key).cloned())
-                .ok_or_else(|| Error::UnresolvedRef(ref_str())),
+/// Replace every color expression in the table with its resolved hex value.
+///
+/// Returns the parsed expressions so their derivation can be explained later.
+fn resolve_table(table: &mut Table) -> Result<BTreeMap<String, ColorExpr>, Error> {
+    let exprs = collect_exprs(table)?;
+
+    let mut resolved: BTreeMap<String, Rgba> = BTreeMap::new();
+    for path in topological_order(&exprs)? {
+        let color = eval_expr(&resolved, &exprs[path])?;
+        resolved.insert(path.to_string(), color);
+    }
+
+    for (path, color) in resolved {
+        let mut keys: Vec<&str> = path.split('.').collect();
+        let leaf = keys.pop().unwrap_or_default();
+        let mut current = &mut *table;
+        for key in keys {
+            match current.get_mut(key) {
+                Some(Value::Table(nested)) => current = nested,
+                _ => return Err(Error::UnresolvedRef(path.clone())),
+            }
         }
+        current.insert(leaf.to_string(), Value::String(color.to_string()));
     }
+
+    Ok(exprs)
 }
 
 impl RawPalette {
-    fn resolve(&self, variant: Variant) -> Result<Palette, Error> {
-        let resolver = Resolver::new(self)?;
-        Ok(Palette {
+    fn into_palette(self, variant: Variant, exprs: BTreeMap<String, ColorExpr>) -> Palette {
+        Palette {
             variant,
-            name: self.name.clone(),
-            description: self.description.clone(),
-            colors: self.colors.clone(),
-            base: self.base.clone(),
-            layers: self.layers.resolve(&resolver)?,
-            state: self.state.resolve(&resolver)?,
-            semantic: self.semantic.resolve(&resolver)?,
-            ansi: resolver.resolved_ansi,
-            ansi_bright: resolver.resolved_ansi_bright,
-        })
+            name: self.name,
+            description: self.description,
+            colors: self.colors,
+            base: self.base,
+            layers: self.layers,
+            state: self.state,
+            semantic: self.semantic,
+            ansi: self.ansi.base,
+            ansi_bright: self.ansi.bright,
+            exprs,
+        }
     }
 }
 
@@ -616,14 +614,17 @@ pub struct Palette {
     pub semantic: Semantic,
     pub ansi: Ansi,
     pub ansi_bright: Ansi,
+    /// Source expression of every color, keyed by path (see [`Palette::explain`])
+    #[serde(skip)]
+    pub(crate) exprs: BTreeMap<String, ColorExpr>,
 }
 
 impl Palette {
     /// Embedded Night palette TOML content.
-    const NIGHT_TOML: &'static str = include_str!("../palette/akari-night.toml");
+    pub(crate) const NIGHT_TOML: &'static str = include_str!("../palette/akari-night.toml");
 
     /// Embedded Dawn palette TOML content.
-    const DAWN_TOML: &'static str = include_str!("../palette/akari-dawn.toml");
+    pub(crate) const DAWN_TOML: &'static str = include_str!("../palette/akari-dawn.toml");
 
     /// Returns the embedded Night palette.
     ///
@@ -632,7 +633,7 @@ impl Palette {
     /// Panics if the embedded palette is invalid (should never happen in normal use).
     #[must_use]
     pub fn night() -> Self {
-        Self::from_str(Self::NIGHT_TOML, Variant::Night)
+        Self::from_str(Self::NIGHT_TOML, Variant::NIGHT)
             .expect("embedded Night palette should be valid")
     }
 
@@ -643,20 +644,174 @@ impl Palette {
     /// Panics if the embedded palette is invalid (should never happen in normal use).
     #[must_use]
     pub fn dawn() -> Self {
-        Self::from_str(Self::DAWN_TOML, Variant::Dawn)
+        Self::from_str(Self::DAWN_TOML, Variant::DAWN)
             .expect("embedded Dawn palette should be valid")
     }
 
     /// Load palette from a file path.
+    ///
+    /// Relative `extends` paths are resolved against the file's directory.
     pub fn from_path(path: impl AsRef<Path>, variant: Variant) -> Result<Self, Error> {
+        let path = path.as_ref();
         let content = fs::read_to_string(path)?;
-        Self::from_str(&content, variant)
+        let mut chain = vec![fs::canonicalize(path)?.display().to_string()];
+        let table = Self::load_table(&content, path.parent(), &mut chain)?;
+        Self::from_table(table, variant)
     }
 
     /// Parse palette from TOML string content.
+    ///
+    /// Relative `extends` paths are resolved against the current directory.
     pub fn from_str(content: &str, variant: Variant) -> Result<Self, Error> {
-        let raw: RawPalette = toml::from_str(content)?;
-        raw.resolve(variant)
+        let table = Self::load_table(content, None, &mut Vec::new())?;
+        Self::from_table(table, variant)
+    }
+
+    /// Load every `akari-{name}.toml` palette in a directory, sorted by variant name.
+    ///
+    /// The variant is taken from the file name, so adding a variant is a matter of
+    /// dropping a new palette file into the directory.
+    pub fn discover(dir: impl AsRef<Path>) -> Result<Vec<Self>, Error> {
+        let mut found = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if let Some(variant) = Variant::from_palette_path(&path) {
+                found.push((variant, path));
+            }
+        }
+        found.sort_by(|(a, _), (b, _)| a.name().cmp(b.name()));
+
+        found
+            .into_iter()
+            .map(|(variant, path)| Self::from_path(path, variant))
+            .collect()
+    }
+
+    fn from_table(mut table: Table, variant: Variant) -> Result<Self, Error> {
+        let exprs = resolve_table(&mut table)?;
+        let raw: RawPalette = table.try_into()?;
+        let palette = raw.into_palette(variant, exprs);
+
+        // Only layers, state and semantic keep extra keys; anywhere else they
+        // would be dropped silently, so reject them (usually a typo)
+        if let Some(path) = palette
+            .exprs
+            .keys()
+            .find(|path| palette.get(path).is_none())
+        {
+            return Err(Error::UnknownKey(path.clone()));
+        }
+        // Extra keys must be nameable in expressions, like the built-in ones
+        if let Some(path) = palette
+            .exprs
+            .keys()
+            .find(|path| !path.split('.').all(is_ident))
+        {
+            return Err(Error::InvalidKey(path.clone()));
+        }
+        Ok(palette)
+    }
+
+    /// Parse TOML content, merging it over the palette named by `extends` if present.
+    ///
+    /// `extends` names an embedded palette (`"akari-night"`, `"akari-dawn"`) or a
+    /// path to a palette file (`.toml` may be omitted). `chain` tracks the files
+    /// being loaded so that a loop of `extends` is reported instead of recursing.
+    fn load_table(
+        content: &str,
+        base_dir: Option<&Path>,
+        chain: &mut Vec<String>,
+    ) -> Result<Table, Error> {
+        let mut table: Table = toml::from_str(content)?;
+        let Some(parent) = table.remove("extends") else {
+            return Ok(table);
+        };
+        let parent = parent
+            .as_str()
+            .ok_or_else(|| Error::ParentNotFound(parent.to_string()))?
+            .to_string();
+
+        let mut base = if let Some(embedded) = Self::embedded_toml(&parent) {
+            Self::load_table(embedded, None, chain)?
+        } else {
+            // Canonical, so a loop through `..` or symlinks still repeats a key
+            let path = fs::canonicalize(Self::parent_path(&parent, base_dir)?)?;
+            let key = path.display().to_string();
+            if chain.contains(&key) {
+                chain.push(key);
+                return Err(Error::CyclicRef(chain.clone()));
+            }
+            chain.push(key);
+            let content = fs::read_to_string(&path)?;
+            let table = Self::load_table(&content, path.parent(), chain)?;
+            chain.pop();
+            table
+        };
+
+        merge_tables(&mut base, table);
+        Ok(base)
+    }
+
+    /// Embedded palette TOML by palette name.
+    fn embedded_toml(name: &str) -> Option<&'static str> {
+        match name {
+            "akari-night" => Some(Self::NIGHT_TOML),
+            "akari-dawn" => Some(Self::DAWN_TOML),
+            _ => None,
+        }
+    }
+
+    /// Locate the file named by `extends`, trying a `.toml` suffix if omitted.
+    fn parent_path(name: &str, base_dir: Option<&Path>) -> Result<PathBuf, Error> {
+        let path = base_dir.map_or_else(|| PathBuf::from(name), |dir| dir.join(name));
+        if path.is_file() {
+            return Ok(path);
+        }
+        let with_ext = path.with_extension("toml");
+        if path.extension().is_none() && with_ext.is_file() {
+            return Ok(with_ext);
+        }
+        Err(Error::ParentNotFound(name.to_string()))
+    }
+
+    /// Iterate over every resolved color as `(key path, color)` pairs.
+    ///
+    /// Paths use the same form as palette references, e.g. `"semantic.keyword"`,
+    /// `"colors.lantern.mid"` or `"ansi.bright.red"`, in section order.
+    pub fn iter(&self) -> impl Iterator<Item = (String, Rgba)> {
+        fn prefixed<'a>(
+            section: &'static str,
+            iter: impl IntoIterator<Item = (&'a str, Rgba)>,
+        ) -> impl Iterator<Item = (String, Rgba)> {
+            iter.into_iter()
+                .map(move |(key, color)| (format!("{section}.{key}"), color))
+        }
+
+        prefixed("colors", &self.colors)
+            .chain(prefixed("base", &self.base))
+            .chain(prefixed("layers", &self.layers))
+            .chain(prefixed("state", &self.state))
+            .chain(prefixed("semantic", &self.semantic))
+            .chain(prefixed("ansi", &self.ansi))
+            .chain(prefixed("ansi.bright", &self.ansi_bright))
+    }
+
+    /// Look up a resolved color by key path (e.g. `"state.cursor"`).
+    #[must_use]
+    pub fn get(&self, path: &str) -> Option<Rgba> {
+        let (section, key) = path.split_once('.')?;
+        match section {
+            "colors" => self.colors.get(key),
+            "base" => self.base.get(key),
+            "layers" => self.layers.get(key),
+            "state" => self.state.get(key),
+            "semantic" => self.semantic.get(key),
+            "ansi" => match key.strip_prefix("bright.") {
+                Some(key) => self.ansi_bright.get(key),
+                None => self.ansi.get(key),
+            },
+            _ => None,
+        }
     }
 }
 
@@ -671,43 +826,43 @@ mod tests {
 
     #[test]
     fn load_night_palette() {
-        let palette = Palette::from_path(palette_path(), Variant::Night).unwrap();
+        let palette = Palette::from_path(palette_path(), Variant::NIGHT).unwrap();
         assert_eq!(palette.name, "akari-night");
-        assert_eq!(palette.variant, Variant::Night);
+        assert_eq!(palette.variant, Variant::NIGHT);
     }
 
     #[test]
     fn colors_are_loaded() {
-        let palette = Palette::from_path(palette_path(), Variant::Night).unwrap();
-        assert_eq!(palette.colors.lantern.mid, "#E26A3B");
-        assert_eq!(palette.colors.lantern.ember, "#D65A3A");
-        assert_eq!(palette.colors.lantern.near, "#D25046");
-        assert_eq!(palette.colors.lantern.far, "#D4A05A");
+        let palette = Palette::from_path(palette_path(), Variant::NIGHT).unwrap();
+        assert_eq!(palette.colors.lantern.mid.to_string(), "#E26A3B");
+        assert_eq!(palette.colors.lantern.ember.to_string(), "#D65A3A");
+        assert_eq!(palette.colors.lantern.near.to_string(), "#D25046");
+        assert_eq!(palette.colors.lantern.far.to_string(), "#D4A05A");
     }
 
     #[test]
     fn base_colors_are_loaded() {
-        let palette = Palette::from_path(palette_path(), Variant::Night).unwrap();
-        assert_eq!(palette.base.background, "#25231F");
-        assert_eq!(palette.base.foreground, "#E6DED3");
+        let palette = Palette::from_path(palette_path(), Variant::NIGHT).unwrap();
+        assert_eq!(palette.base.background.to_string(), "#25231F");
+        assert_eq!(palette.base.foreground.to_string(), "#E6DED3");
     }
 
     #[test]
     fn semantic_references_resolved() {
-        let palette = Palette::from_path(palette_path(), Variant::Night).unwrap();
+        let palette = Palette::from_path(palette_path(), Variant::NIGHT).unwrap();
         // semantic.keyword = "colors.lantern.mid" -> "#E26A3B"
-        assert_eq!(palette.semantic.keyword, "#E26A3B");
+        assert_eq!(palette.semantic.keyword.to_string(), "#E26A3B");
         // semantic.string = "colors.life" -> "#7FAF6A"
-        assert_eq!(palette.semantic.string, "#7FAF6A");
+        assert_eq!(palette.semantic.string.to_string(), "#7FAF6A");
     }
 
     #[test]
     fn ansi_references_resolved() {
-        let palette = Palette::from_path(palette_path(), Variant::Night).unwrap();
+        let palette = Palette::from_path(palette_path(), Variant::NIGHT).unwrap();
         // ansi.green = "colors.life" -> "#7FAF6A"
-        assert_eq!(palette.ansi.green, "#7FAF6A");
+        assert_eq!(palette.ansi.green.to_string(), "#7FAF6A");
         // ansi.white = "base.foreground" -> "#E6DED3"
-        assert_eq!(palette.ansi.white, "#E6DED3");
+        assert_eq!(palette.ansi.white.to_string(), "#E6DED3");
     }
 
     #[test]
@@ -793,7 +948,7 @@ white = "base.foreground"
         let mut file = NamedTempFile::new().unwrap();
         file.write_all(toml.as_bytes()).unwrap();
 
-        let result = Palette::from_path(file.path(), Variant::Night);
+        let result = Palette::from_path(file.path(), Variant::NIGHT);
         assert!(result.is_err());
         let err = result.unwrap_err();
         assert!(matches!(err, Error::ParsePalette(_)));
@@ -893,116 +1048,316 @@ white = "base.foreground"
         let mut file = NamedTempFile::new().unwrap();
         file.write_all(toml.as_bytes()).unwrap();
 
-        let result = Palette::from_path(file.path(), Variant::Night);
+        let result = Palette::from_path(file.path(), Variant::NIGHT);
         assert!(result.is_err());
         let err = result.unwrap_err();
         assert!(matches!(err, Error::UnresolvedRef(_)));
     }
 
     #[test]
-    fn parse_color_expr_literal() {
-        let expr = parse_color_expr("#E26A3B").unwrap();
-        assert!(matches!(expr, ColorExpr::Literal(s) if s == "#E26A3B"));
+    fn resolve_alpha_carries_through_functions() {
+        struct Fixed;
+        impl ResolveRef for Fixed {
+            fn resolve_ref(&self, _section: Section, _key: &str) -> Result<Rgba, Error> {
+                "#646464".parse()
+            }
+        }
+
+        let expr = parse_color_expr("darken(alpha(colors.night, 0.5), 0.5)").unwrap();
+        assert_eq!(eval_expr(&Fixed, &expr).unwrap().to_string(), "#32323280");
+
+        let expr = parse_color_expr("alpha(colors.night, 1.0)").unwrap();
+        assert_eq!(eval_expr(&Fixed, &expr).unwrap().to_string(), "#646464");
+    }
+
+    #[test]
+    fn get_by_key_path() {
+        let palette = Palette::from_path(palette_path(), Variant::NIGHT).unwrap();
+        assert_eq!(palette.get("colors.lantern.mid"), "#E26A3B".parse().ok());
+        assert_eq!(palette.get("semantic.type"), "#D4A05A".parse().ok());
+        assert_eq!(
+            palette.get("ansi.bright.white"),
+            Some(palette.ansi_bright.white)
+        );
+        assert_eq!(palette.get("semantic.nonexistent"), None);
+        assert_eq!(palette.get("colors.lantern"), None);
+        assert_eq!(palette.get("ansi.bright"), None);
+        assert_eq!(palette.get("semantic"), None);
+
+        for (path, color) in palette.iter() {
+            assert_eq!(palette.get(&path), Some(color), "{path}");
+        }
     }
 
     #[test]
-    fn parse_color_expr_reference() {
-        let expr = parse_color_expr("colors.lantern.mid").unwrap();
+    fn iter_covers_every_section() {
+        let palette = Palette::night();
+        let entries: Vec<_> = palette.iter().collect();
+
+        assert_eq!(entries.len(), 8 + 2 + 6 + 17 + 16 + 8 + 8);
+        assert_eq!(
+            entries[0],
+            (
+                "colors.lantern.ember".to_string(),
+                palette.colors.lantern.ember
+            )
+        );
+        assert!(entries.contains(&("ansi.bright.red".to_string(), palette.ansi_bright.red)));
         assert!(
-            matches!(expr, ColorExpr::Ref { section, key } if section == Section::Colors && key == "lantern.mid")
+            entries
+                .iter()
+                .all(|(key, color)| palette.get(key) == Some(*color))
         );
     }
 
     #[test]
-    fn parse_color_expr_lighten() {
-        let expr = parse_color_expr("lighten(colors.lantern.mid, 0.1)").unwrap();
-        match expr {
-            ColorExpr::Lighten(inner, factor) => {
-                assert!(
-                    matches!(*inner, ColorExpr::Ref { section, key } if section == Section::Colors && key == "lantern.mid")
-                );
-                assert!((factor - 0.1).abs() < 0.001);
+    fn resolve_ensure_contrast() {
+        struct Fixed;
+        impl ResolveRef for Fixed {
+            fn resolve_ref(&self, section: Section, _key: &str) -> Result<Rgba, Error> {
+                match section {
+                    Section::Base => "#F5EFE6",
+                    _ => "#D4A05A",
+                }
+                .parse()
             }
-            _ => panic!("expected Lighten"),
         }
+
+        let expr =
+            parse_color_expr("ensure_contrast(colors.lantern.far, base.background, 4.5)").unwrap();
+        let fg = eval_expr(&Fixed, &expr).unwrap().rgb();
+        let bg: crate::Rgb = "#F5EFE6".parse().unwrap();
+        assert!(fg.contrast_ratio(bg) >= 4.5);
     }
 
     #[test]
-    fn parse_color_expr_darken() {
-        let expr = parse_color_expr("darken(base.background, 0.2)").unwrap();
-        match expr {
-            ColorExpr::Darken(inner, factor) => {
-                assert!(
-                    matches!(*inner, ColorExpr::Ref { section, key } if section == Section::Base && key == "background")
-                );
-                assert!((factor - 0.2).abs() < 0.001);
+    fn invalid_expression_reports_key_path() {
+        let content = Palette::NIGHT_TOML.replace(
+            r#"comment = "lighten(colors.night, 0.25)""#,
+            r#"comment = "lighten(colors.night 0.25)""#,
+        );
+        let err = Palette::from_str(&content, Variant::NIGHT).unwrap_err();
+        match err {
+            Error::InvalidColorExpr(e) => {
+                assert_eq!(e.key.as_deref(), Some("semantic.comment"));
+                assert_eq!(e.column(), 22);
             }
-            _ => panic!("expected Darken"),
+            _ => panic!("expected InvalidColorExpr, got {err:?}"),
         }
     }
 
     #[test]
-    fn parse_color_expr_nested() {
-        let expr = parse_color_expr("lighten(darken(colors.lantern.mid, 0.1), 0.2)").unwrap();
-        match expr {
-            ColorExpr::Lighten(inner, outer_factor) => {
-                assert!((outer_factor - 0.2).abs() < 0.001);
-                match *inner {
-                    ColorExpr::Darken(innermost, inner_factor) => {
-                        assert!(
-                            matches!(*innermost, ColorExpr::Ref { section, key } if section == Section::Colors && key == "lantern.mid")
-                        );
-                        assert!((inner_factor - 0.1).abs() < 0.001);
-                    }
-                    _ => panic!("expected Darken"),
-                }
-            }
-            _ => panic!("expected Lighten"),
-        }
+    fn references_across_sections() {
+        let content = Palette::NIGHT_TOML.replace(
+            r#"surface = "mix(base.background, colors.night, 0.15)""#,
+            r#"surface = "mix(layers.base, state.info, 0.15)""#,
+        );
+        let palette = Palette::from_str(&content, Variant::NIGHT).unwrap();
+        let night = Palette::night();
+        assert_eq!(palette.state.hint, night.semantic.comment);
+        assert_eq!(palette.layers.surface, night.layers.surface);
     }
 
     #[test]
-    fn parse_color_expr_mix() {
-        let expr = parse_color_expr("mix(base.background, colors.night, 0.15)").unwrap();
-        match expr {
-            ColorExpr::Mix(color1, color2, factor) => {
-                assert!(
-                    matches!(*color1, ColorExpr::Ref { section, key } if section == Section::Base && key == "background")
-                );
-                assert!(
-                    matches!(*color2, ColorExpr::Ref { section, key } if section == Section::Colors && key == "night")
-                );
-                assert!((factor - 0.15).abs() < 0.001);
-            }
-            _ => panic!("expected Mix"),
+    fn reference_cycle_is_reported() {
+        // state.hint = "semantic.comment" in the shipped palette
+        let content = Palette::NIGHT_TOML
+            .replace(
+                r#"comment = "lighten(colors.night, 0.25)""#,
+                r#"comment = "lighten(layers.surface, 0.25)""#,
+            )
+            .replace(
+                r#"surface = "mix(base.background, colors.night, 0.15)""#,
+                r#"surface = "mix(base.background, state.hint, 0.15)""#,
+            );
+        let err = Palette::from_str(&content, Variant::NIGHT).unwrap_err();
+        match err {
+            Error::CyclicRef(cycle) => assert_eq!(
+                cycle,
+                [
+                    "layers.surface",
+                    "state.hint",
+                    "semantic.comment",
+                    "layers.surface"
+                ]
+            ),
+            _ => panic!("expected CyclicRef, got {err:?}"),
         }
     }
 
     #[test]
-    fn parse_color_expr_rejects_non_referenceable_sections() {
-        // layers, state, semantic exist in palette but cannot be referenced
-        let err = parse_color_expr("layers.base").unwrap_err();
-        assert!(
-            matches!(err, Error::InvalidColorExpr(msg) if msg.contains("cannot be referenced"))
+    fn self_reference_is_a_cycle() {
+        let content = Palette::NIGHT_TOML.replace(
+            r#"cursor = "colors.lantern.mid""#,
+            r#"cursor = "lighten(state.cursor, 0.1)""#,
         );
-
-        let err = parse_color_expr("state.cursor").unwrap_err();
-        assert!(
-            matches!(err, Error::InvalidColorExpr(msg) if msg.contains("cannot be referenced"))
+        let err = Palette::from_str(&content, Variant::NIGHT).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "reference cycle: state.cursor -> state.cursor"
         );
+    }
 
-        let err = parse_color_expr("semantic.keyword").unwrap_err();
-        assert!(
-            matches!(err, Error::InvalidColorExpr(msg) if msg.contains("cannot be referenced"))
-        );
+    #[test]
+    fn extends_embedded_palette() {
+        let toml = r##"
+extends = "akari-night"
+name = "akari-night-green"
+
+[base]
+background = "#1F2320"
+
+[semantic]
+string = "lighten(colors.life, 0.1)"
+"##;
+        let palette = Palette::from_str(toml, Variant::NIGHT).unwrap();
+        let night = Palette::night();
+
+        assert_eq!(palette.name, "akari-night-green");
+        assert_eq!(palette.description, night.description);
+        assert_eq!(palette.base.background.to_string(), "#1F2320");
+        assert_eq!(palette.base.foreground, night.base.foreground);
+        // Inherited expressions resolve against the overridden values
+        assert_eq!(palette.layers.base.to_string(), "#1F2320");
+        assert_ne!(palette.semantic.string, night.semantic.string);
+        assert_eq!(palette.semantic.keyword, night.semantic.keyword);
     }
 
     #[test]
-    fn parse_color_expr_ansi_bright() {
-        // ansi.bright.* is parsed as Section::Ansi with key "bright.*"
-        let expr = parse_color_expr("ansi.bright.red").unwrap();
+    fn extends_relative_path() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("base.toml"), Palette::DAWN_TOML).unwrap();
+        fs::write(
+            dir.path().join("child.toml"),
+            "extends = \"base\"\n\n[colors]\nlife = \"#5F9F4A\"\n",
+        )
+        .unwrap();
+
+        let palette = Palette::from_path(dir.path().join("child.toml"), Variant::DAWN).unwrap();
+        assert_eq!(palette.name, "akari-dawn");
+        assert_eq!(palette.colors.life.to_string(), "#5F9F4A");
+        assert_eq!(palette.ansi.green.to_string(), "#5F9F4A");
+    }
+
+    #[test]
+    fn extends_missing_parent_fails() {
+        let err = Palette::from_str("extends = \"akari-nite\"", Variant::NIGHT).unwrap_err();
+        assert!(matches!(err, Error::ParentNotFound(name) if name == "akari-nite"));
+    }
+
+    #[test]
+    fn extends_cycle_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.toml"), "extends = \"b.toml\"").unwrap();
+        fs::write(dir.path().join("b.toml"), "extends = \"a.toml\"").unwrap();
+
+        let err = Palette::from_path(dir.path().join("a.toml"), Variant::NIGHT).unwrap_err();
+        assert!(matches!(err, Error::CyclicRef(chain) if chain.len() == 3));
+    }
+
+    #[test]
+    fn extends_cycle_through_parent_dir_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let sub = dir.path().join("dir");
+        fs::create_dir(&sub).unwrap();
+        fs::write(sub.join("a.toml"), "extends = \"../dir/a.toml\"").unwrap();
+
+        let err = Palette::from_path(sub.join("a.toml"), Variant::NIGHT).unwrap_err();
+        assert!(matches!(err, Error::CyclicRef(chain) if chain.len() == 2));
+    }
+
+    #[test]
+    fn discover_finds_variant_files() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("akari-night.toml"), Palette::NIGHT_TOML).unwrap();
+        fs::write(
+            dir.path().join("akari-dusk.toml"),
+            "extends = \"akari-night\"\nname = \"akari-dusk\"\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("lint.toml"), "").unwrap();
+
+        let palettes = Palette::discover(dir.path()).unwrap();
+        let variants: Vec<_> = palettes.iter().map(|p| p.variant.name()).collect();
+        assert_eq!(variants, ["dusk", "night"]);
+        assert_eq!(palettes[0].name, "akari-dusk");
+    }
+
+    #[test]
+    fn extra_keys_are_resolved() {
+        let toml = r##"
+extends = "akari-night"
+
+[layers]
+overlay = "darken(layers.base, 0.2)"
+
+[state]
+diff_text_bg = "alpha(state.diff_changed, 0.25)"
+active_bg = "layers.overlay"
+
+[semantic]
+operator = "mix(semantic.keyword, semantic.text, 0.5)"
+"##;
+        let palette = Palette::from_str(toml, Variant::NIGHT).unwrap();
+
+        let overlay = palette.layers.extra["overlay"];
+        assert_eq!(palette.get("layers.overlay"), Some(overlay));
+        // Built-in keys may reference extra keys
+        assert_eq!(palette.state.active_bg, overlay);
+        assert_eq!(palette.state.extra["diff_text_bg"].a, 0x40);
+        assert!(palette.get("semantic.operator").is_some());
+        assert!(palette.iter().any(|(key, _)| key == "state.diff_text_bg"));
+        assert!(palette.explain("semantic.operator").is_some());
+
+        // Extra keys serialize alongside the built-in ones for templates
+        let semantic = toml::to_string(&palette.semantic).unwrap();
+        assert!(semantic.contains(&format!(
+            "operator = \"{}\"",
+            palette.semantic.extra["operator"]
+        )));
+    }
+
+    #[test]
+    fn extra_key_must_be_a_color() {
+        let toml = "extends = \"akari-night\"\n\n[semantic]\noperator = \"#12\"\n";
+        let err = Palette::from_str(toml, Variant::NIGHT).unwrap_err();
         assert!(
-            matches!(expr, ColorExpr::Ref { section, key } if section == Section::Ansi && key == "bright.red")
+            matches!(err, Error::InvalidColorExpr(e) if e.key.as_deref() == Some("semantic.operator"))
         );
     }
+
+    #[test]
+    fn extra_keys_outside_open_sections_are_rejected() {
+        for (section, key) in [
+            ("colors", "colors.dusk"),
+            ("base", "base.accent"),
+            ("ansi", "ansi.orange"),
+        ] {
+            let toml = format!(
+                "extends = \"akari-night\"\n\n[{section}]\n{} = \"#123456\"\n",
+                key.rsplit('.').next().unwrap()
+            );
+            let err = Palette::from_str(&toml, Variant::NIGHT).unwrap_err();
+            assert!(matches!(err, Error::UnknownKey(path) if path == key));
+        }
+
+        let toml = "extends = \"akari-night\"\n\n[ansi.bright]\norange = \"#123456\"\n";
+        let err = Palette::from_str(toml, Variant::NIGHT).unwrap_err();
+        assert!(matches!(err, Error::UnknownKey(path) if path == "ansi.bright.orange"));
+    }
+
+    #[test]
+    fn extra_keys_must_be_identifiers() {
+        for key in ["my-role", "2nd", "\"with space\""] {
+            let toml = format!("extends = \"akari-night\"\n\n[semantic]\n{key} = \"#123456\"\n");
+            let err = Palette::from_str(&toml, Variant::NIGHT).unwrap_err();
+            assert!(
+                matches!(&err, Error::InvalidKey(path) if path.starts_with("semantic.")),
+                "{key}: {err:?}"
+            );
+        }
+
+        let toml = "extends = \"akari-night\"\n\n[semantic]\nmy_role2 = \"#123456\"\n";
+        assert!(Palette::from_str(toml, Variant::NIGHT).is_ok());
+    }
 }
@@ -0,0 +1,199 @@
+//! JSON Schema for palette TOML files, for editor validation (e.g. Taplo).
+
+use crate::palette::{Ansi, Base, Colors, Layers, Semantic, State};
+use serde_json::{Map, Value, json};
+
+/// Sections that accept keys beyond the built-in ones (see `extra` fields).
+const OPEN_SECTIONS: [&str; 3] = ["layers", "state", "semantic"];
+
+/// Approximate shape of a color expression: hex literal, key path or function call.
+const EXPR_PATTERN: &str = r"^\s*(#([0-9A-Fa-f]{6}|[0-9A-Fa-f]{8})|[A-Za-z_][A-Za-z0-9_]*(\.[A-Za-z_][A-Za-z0-9_]*)+|[a-z_]+\s*\(.*\))\s*$";
+
+/// Palette keys, matching the identifiers color expressions can refer to.
+const KEY_PATTERN: &str = r"^[A-Za-z_][A-Za-z0-9_]*$";
+
+/// Build the JSON Schema (draft-07) describing a palette file.
+///
+/// Key lists come from the section types' `KEYS`, so the schema tracks the
+/// Rust definitions rather than any palette's data. Every built-in key is
+/// required unless the file uses `extends`; additional keys are only allowed
+/// in [`OPEN_SECTIONS`].
+#[must_use]
+pub fn palette_schema() -> Value {
+    let section_keys: [(&str, &[&str]); 7] = [
+        ("colors", &Colors::KEYS),
+        ("base", &Base::KEYS),
+        ("layers", &Layers::KEYS),
+        ("state", &State::KEYS),
+        ("semantic", &Semantic::KEYS),
+        ("ansi", &Ansi::KEYS),
+        ("ansi.bright", &Ansi::KEYS),
+    ];
+
+    let mut sections = Map::new();
+    for (section, keys) in section_keys {
+        for key in keys {
+            let path = format!("{section}.{key}");
+            let mut node = &mut sections;
+            let mut parts = path.split('.').peekable();
+            while let Some(part) = parts.next() {
+                if parts.peek().is_none() {
+                    node.insert(part.to_string(), Value::Null);
+                    break;
+                }
+                node = node
+                    .entry(part)
+                    .or_insert_with(|| Value::Object(Map::new()))
+                    .as_object_mut()
+                    .expect("palette paths never nest under a color");
+            }
+        }
+    }
+
+    let mut properties = Map::new();
+    let mut required_sections = Map::new();
+    for (section, keys) in &sections {
+        let Value::Object(keys) = keys else { continue };
+        let open = OPEN_SECTIONS.contains(&section.as_str());
+        properties.insert(section.clone(), table_schema(keys, open, false));
+        required_sections.insert(section.clone(), table_schema(keys, open, true));
+    }
+    let mut required: Vec<&str> = vec!["name", "description"];
+    required.extend(sections.keys().map(String::as_str));
+
+    properties.insert(
+        "extends".to_string(),
+        json!({
+            "type": "string",
+            "description": "Palette to inherit from: \"akari-night\", \"akari-dawn\" or a path to a palette file. Only overridden keys need to be listed."
+        }),
+    );
+    properties.insert("name".to_string(), json!({ "type": "string" }));
+    properties.insert("description".to_string(), json!({ "type": "string" }));
+
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "Akari palette",
+        "type": "object",
+        "properties": properties,
+        "additionalProperties": false,
+        "if": { "required": ["extends"] },
+        "else": {
+            "required": required,
+            "properties": required_sections,
+        },
+        "definitions": {
+            "color": {
+                "type": "string",
+                "pattern": EXPR_PATTERN,
+                "description": "Hex color (#RRGGBB or #RRGGBBAA), key reference (semantic.comment) or function (lighten(colors.night, 0.25))"
+            }
+        }
+    })
+}
+
+/// Schema for a table of colors; with `required`, only the required-key constraints.
+fn table_schema(keys: &Map<String, Value>, open: bool, required: bool) -> Value {
+    let mut properties = Map::new();
+    for (key, value) in keys {
+        let schema = match value {
+            Value::Object(nested) => table_schema(nested, false, required),
+            _ if required => continue,
+            _ => json!({ "$ref": "#/definitions/color" }),
+        };
+        properties.insert(key.clone(), schema);
+    }
+
+    if required {
+        return json!({
+            "required": keys.keys().collect::<Vec<_>>(),
+            "properties": properties,
+        });
+    }
+
+    if open {
+        return json!({
+            "type": "object",
+            "properties": properties,
+            "propertyNames": { "pattern": KEY_PATTERN },
+            "additionalProperties": { "$ref": "#/definitions/color" },
+        });
+    }
+    json!({
+        "type": "object",
+        "properties": properties,
+        "additionalProperties": false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn schema_lists_every_section() {
+        let schema = palette_schema();
+        let required = schema["else"]["required"].as_array().unwrap();
+        for key in [
+            "name",
+            "description",
+            "colors",
+            "base",
+            "layers",
+            "state",
+            "semantic",
+            "ansi",
+        ] {
+            assert!(required.contains(&json!(key)), "{key} should be required");
+            assert!(schema["properties"][key].is_object());
+        }
+    }
+
+    #[test]
+    fn schema_nested_tables() {
+        let schema = palette_schema();
+        let colors = &schema["properties"]["colors"];
+        assert_eq!(colors["additionalProperties"], json!(false));
+        assert_eq!(
+            colors["properties"]["lantern"]["properties"]["ember"]["$ref"],
+            "#/definitions/color"
+        );
+
+        let bright = &schema["else"]["properties"]["ansi"]["properties"]["bright"];
+        assert_eq!(bright["required"].as_array().unwrap().len(), 8);
+    }
+
+    #[test]
+    fn schema_allows_extra_keys_in_open_sections() {
+        let schema = palette_schema();
+        let semantic = &schema["properties"]["semantic"];
+        assert_eq!(
+            semantic["additionalProperties"]["$ref"],
+            "#/definitions/color"
+        );
+        // Extra keys must be nameable in expressions, as the loader requires
+        assert_eq!(semantic["propertyNames"]["pattern"], KEY_PATTERN);
+        assert_eq!(
+            schema["properties"]["base"]["additionalProperties"],
+            json!(false)
+        );
+    }
+
+    #[test]
+    fn required_keys_come_from_section_types() {
+        let schema = palette_schema();
+        let mut required: Vec<&str> = schema["else"]["properties"]["semantic"]["required"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|key| key.as_str().unwrap())
+            .collect();
+        required.sort_unstable();
+        let mut keys = Semantic::KEYS;
+        keys.sort_unstable();
+        assert_eq!(required, keys);
+
+        let colors = &schema["properties"]["colors"]["properties"];
+        assert_eq!(colors.as_object().unwrap().len(), 5);
+    }
+}
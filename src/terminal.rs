@@ -1,4 +1,4 @@
-use crate::{Error, Palette, Rgb};
+use crate::{Error, Palette, Rgba};
 use plist::Value;
 use std::collections::BTreeMap;
 use std::io::Cursor;
@@ -10,9 +10,8 @@ fn capitalize(s: &str) -> String {
         .unwrap_or_default()
 }
 
-fn encode_nscolor(hex: &str) -> Result<Vec<u8>, Error> {
-    let rgb: Rgb = hex.parse()?;
-    let (r, g, b) = rgb.as_floats();
+fn encode_nscolor(color: Rgba) -> Result<Vec<u8>, Error> {
+    let (r, g, b) = color.rgb().as_floats();
     let nsrgb = format!("{r} {g} {b}");
 
     let color_obj = BTreeMap::from([
@@ -64,8 +63,8 @@ fn encode_nscolor(hex: &str) -> Result<Vec<u8>, Error> {
     Ok(buf.into_inner())
 }
 
-fn color_data(hex: &str) -> Result<Value, Error> {
-    encode_nscolor(hex).map(Value::Data)
+fn color_data(color: Rgba) -> Result<Value, Error> {
+    encode_nscolor(color).map(Value::Data)
 }
 
 pub fn generate(palette: &Palette) -> Result<String, Error> {
@@ -74,49 +73,46 @@ pub fn generate(palette: &Palette) -> Result<String, Error> {
     let mut dict: BTreeMap<String, Value> = BTreeMap::new();
 
     // ANSI colors
-    for (name, hex) in &palette.ansi {
+    for (name, color) in &palette.ansi {
         let key = format!("ANSI{}Color", capitalize(name));
-        dict.insert(key, color_data(hex)?);
+        dict.insert(key, color_data(color)?);
     }
 
     // ANSI bright colors
-    for (name, hex) in &palette.ansi_bright {
+    for (name, color) in &palette.ansi_bright {
         let key = format!("ANSIBright{}Color", capitalize(name));
-        dict.insert(key, color_data(hex)?);
+        dict.insert(key, color_data(color)?);
     }
 
     // Base colors
     dict.insert(
         "BackgroundColor".to_string(),
-        color_data(&palette.base.background)?,
+        color_data(palette.base.background)?,
     );
     dict.insert(
         "TextColor".to_string(),
-        color_data(&palette.base.foreground)?,
+        color_data(palette.base.foreground)?,
     );
     dict.insert(
         "TextBoldColor".to_string(),
-        color_data(&palette.base.foreground)?,
+        color_data(palette.base.foreground)?,
     );
 
     // Cursor
-    dict.insert(
-        "CursorColor".to_string(),
-        color_data(&palette.state.cursor)?,
-    );
+    dict.insert("CursorColor".to_string(), color_data(palette.state.cursor)?);
     dict.insert(
         "CursorTextColor".to_string(),
-        color_data(&palette.state.cursor_text)?,
+        color_data(palette.state.cursor_text)?,
     );
 
     // Selection
     dict.insert(
         "SelectionColor".to_string(),
-        color_data(&palette.state.selection_bg)?,
+        color_data(palette.state.selection_bg)?,
     );
     dict.insert(
         "SelectedTextColor".to_string(),
-        color_data(&palette.state.selection_fg)?,
+        color_data(palette.state.selection_fg)?,
     );
 
     dict.insert("name".to_string(), Value::String(name));
@@ -126,6 +122,11 @@ pub fn generate(palette: &Palette) -> Result<String, Error> {
         Value::String("Window Settings".to_string()),
     );
 
+    to_xml(dict)
+}
+
+/// Serialize a top-level dictionary as an XML plist (keys in sorted order).
+pub(crate) fn to_xml(dict: BTreeMap<String, Value>) -> Result<String, Error> {
     let plist_value = Value::Dictionary(dict.into_iter().collect());
     let mut buf = Cursor::new(Vec::new());
     plist_value.to_writer_xml(&mut buf)?;
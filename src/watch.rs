@@ -0,0 +1,102 @@
+//! Polling file watcher used by `akari-gen watch`.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use walkdir::WalkDir;
+
+/// Modification time and size of a file, enough to notice saves.
+type Stamp = (Option<SystemTime>, u64);
+
+/// Tracks the files under a set of directories and reports which changed.
+///
+/// Polling keeps this dependency-free and behaves the same on every platform
+/// and filesystem; the trees watched here are a few dozen files.
+#[derive(Debug)]
+pub struct Watcher {
+    roots: Vec<PathBuf>,
+    stamps: BTreeMap<PathBuf, Stamp>,
+}
+
+impl Watcher {
+    /// Start watching `roots`; files present now are the baseline.
+    pub fn new(roots: impl IntoIterator<Item = impl AsRef<Path>>) -> Self {
+        let roots: Vec<PathBuf> = roots
+            .into_iter()
+            .map(|r| r.as_ref().to_path_buf())
+            .collect();
+        let stamps = scan(&roots);
+        Self { roots, stamps }
+    }
+
+    /// Paths created, modified or removed since the previous call.
+    pub fn changes(&mut self) -> Vec<PathBuf> {
+        let stamps = scan(&self.roots);
+        let mut changed: Vec<PathBuf> = stamps
+            .iter()
+            .filter(|(path, stamp)| self.stamps.get(*path) != Some(stamp))
+            .map(|(path, _)| path.clone())
+            .collect();
+        changed.extend(
+            self.stamps
+                .keys()
+                .filter(|path| !stamps.contains_key(*path))
+                .cloned(),
+        );
+        changed.sort();
+        self.stamps = stamps;
+        changed
+    }
+}
+
+fn scan(roots: &[PathBuf]) -> BTreeMap<PathBuf, Stamp> {
+    roots
+        .iter()
+        .flat_map(WalkDir::new)
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| {
+            let meta = e.metadata().ok()?;
+            Some((e.into_path(), (meta.modified().ok(), meta.len())))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn reports_created_modified_and_removed_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join("a.toml"), "a").unwrap();
+        fs::write(root.join("b.toml"), "b").unwrap();
+
+        let mut watcher = Watcher::new([root]);
+        assert!(watcher.changes().is_empty());
+
+        fs::write(root.join("a.toml"), "a = 1").unwrap();
+        fs::remove_file(root.join("b.toml")).unwrap();
+        fs::create_dir(root.join("sub")).unwrap();
+        fs::write(root.join("sub/c.toml"), "c").unwrap();
+
+        assert_eq!(
+            watcher.changes(),
+            [
+                root.join("a.toml"),
+                root.join("b.toml"),
+                root.join("sub/c.toml")
+            ]
+        );
+        assert!(watcher.changes().is_empty());
+    }
+
+    #[test]
+    fn missing_root_is_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut watcher = Watcher::new([dir.path().join("missing")]);
+        assert!(watcher.changes().is_empty());
+    }
+}
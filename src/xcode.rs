@@ -0,0 +1,174 @@
+use crate::terminal::to_xml;
+use crate::{Error, Palette, Rgba};
+use plist::Value;
+use std::collections::BTreeMap;
+
+/// Editor font for every syntax key; Xcode ignores themes without one.
+const FONT: &str = "SFMono-Regular - 13.0";
+
+/// Xcode color string: "r g b a" floats in 0.0-1.0.
+fn color_string(color: Rgba) -> Value {
+    let (r, g, b) = color.rgb().as_floats();
+    Value::String(format!("{r:.6} {g:.6} {b:.6} {:.6}", color.alpha()))
+}
+
+/// Generate an Xcode theme (`.xccolortheme`) for a palette.
+pub fn generate(palette: &Palette) -> Result<String, Error> {
+    let Palette {
+        base,
+        layers,
+        state,
+        semantic,
+        ..
+    } = palette;
+
+    let syntax = [
+        ("xcode.syntax.plain", base.foreground),
+        ("xcode.syntax.comment", semantic.comment),
+        ("xcode.syntax.comment.doc", semantic.comment),
+        ("xcode.syntax.comment.doc.keyword", semantic.keyword),
+        ("xcode.syntax.mark", semantic.comment),
+        ("xcode.syntax.keyword", semantic.keyword),
+        ("xcode.syntax.string", semantic.string),
+        ("xcode.syntax.character", semantic.string),
+        ("xcode.syntax.number", semantic.number),
+        ("xcode.syntax.regex", semantic.regexp),
+        ("xcode.syntax.url", semantic.link),
+        ("xcode.syntax.attribute", semantic.r#macro),
+        ("xcode.syntax.preprocessor", semantic.r#macro),
+        ("xcode.syntax.identifier.macro", semantic.r#macro),
+        ("xcode.syntax.identifier.macro.system", semantic.r#macro),
+        ("xcode.syntax.declaration.type", semantic.r#type),
+        ("xcode.syntax.declaration.other", semantic.function),
+        ("xcode.syntax.identifier.class", semantic.r#type),
+        ("xcode.syntax.identifier.class.system", semantic.r#type),
+        ("xcode.syntax.identifier.type", semantic.r#type),
+        ("xcode.syntax.identifier.type.system", semantic.r#type),
+        ("xcode.syntax.identifier.function", semantic.function),
+        ("xcode.syntax.identifier.function.system", semantic.function),
+        ("xcode.syntax.identifier.constant", semantic.constant),
+        ("xcode.syntax.identifier.constant.system", semantic.constant),
+        ("xcode.syntax.identifier.variable", semantic.variable),
+        ("xcode.syntax.identifier.variable.system", semantic.variable),
+    ];
+
+    let mut colors = BTreeMap::new();
+    let mut fonts = BTreeMap::new();
+    for (key, color) in syntax {
+        colors.insert(key.to_string(), color_string(color));
+        fonts.insert(key.to_string(), Value::String(FONT.to_string()));
+    }
+
+    let mut dict: BTreeMap<String, Value> = BTreeMap::new();
+    dict.insert(
+        "DVTSourceTextSyntaxColors".to_string(),
+        Value::Dictionary(colors.into_iter().collect()),
+    );
+    dict.insert(
+        "DVTSourceTextSyntaxFonts".to_string(),
+        Value::Dictionary(fonts.into_iter().collect()),
+    );
+
+    // Editor
+    for (key, color) in [
+        ("DVTSourceTextBackground", base.background),
+        ("DVTSourceTextCurrentLineHighlightColor", layers.surface),
+        ("DVTSourceTextInsertionPointColor", state.cursor),
+        ("DVTSourceTextSelectionColor", state.selection_bg),
+        ("DVTSourceTextInvisiblesColor", layers.border),
+        ("DVTSourceTextBlockDimBackgroundColor", layers.sunken),
+        ("DVTMarkupTextBackgroundColor", layers.raised),
+        ("DVTMarkupTextNormalColor", base.foreground),
+        ("DVTMarkupTextLinkColor", semantic.link),
+    ] {
+        dict.insert(key.to_string(), color_string(color));
+    }
+
+    // Console (Xcode's own key names, including the "Exectuable" typo)
+    for (key, color) in [
+        ("DVTConsoleTextBackgroundColor", base.background),
+        ("DVTConsoleTextInsertionPointColor", state.cursor),
+        ("DVTConsoleTextSelectionColor", state.selection_bg),
+        ("DVTConsoleDebuggerInputTextColor", base.foreground),
+        ("DVTConsoleDebuggerOutputTextColor", semantic.comment),
+        ("DVTConsoleDebuggerPromptTextColor", semantic.keyword),
+        ("DVTConsoleExectuableInputTextColor", base.foreground),
+        ("DVTConsoleExectuableOutputTextColor", base.foreground),
+    ] {
+        dict.insert(key.to_string(), color_string(color));
+    }
+
+    dict.insert(
+        "DVTFontAndColorVersion".to_string(),
+        Value::Integer(1.into()),
+    );
+
+    to_xml(dict)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load(content: &str) -> plist::Dictionary {
+        Value::from_reader_xml(content.as_bytes())
+            .unwrap()
+            .into_dictionary()
+            .unwrap()
+    }
+
+    /// Parse an Xcode color string back into 8-bit channels.
+    fn parse_color(value: &Value) -> [u8; 4] {
+        let floats: Vec<f64> = value
+            .as_string()
+            .unwrap()
+            .split(' ')
+            .map(|f| f.parse().unwrap())
+            .collect();
+        assert_eq!(floats.len(), 4);
+        std::array::from_fn(|i| (floats[i] * 255.0).round() as u8)
+    }
+
+    #[test]
+    fn round_trip_through_plist() {
+        let palette = Palette::night();
+        let dict = load(&generate(&palette).unwrap());
+
+        let syntax = dict["DVTSourceTextSyntaxColors"].as_dictionary().unwrap();
+        let comment = palette.semantic.comment;
+        assert_eq!(
+            parse_color(&syntax["xcode.syntax.comment"]),
+            [comment.r, comment.g, comment.b, comment.a]
+        );
+        let keyword = palette.semantic.keyword;
+        assert_eq!(
+            parse_color(&syntax["xcode.syntax.keyword"]),
+            [keyword.r, keyword.g, keyword.b, keyword.a]
+        );
+
+        let background = palette.base.background;
+        assert_eq!(
+            parse_color(&dict["DVTSourceTextBackground"]),
+            [background.r, background.g, background.b, 255]
+        );
+        assert_eq!(dict["DVTFontAndColorVersion"].as_signed_integer(), Some(1));
+    }
+
+    #[test]
+    fn every_syntax_color_has_a_font() {
+        let dict = load(&generate(&Palette::dawn()).unwrap());
+        let colors = dict["DVTSourceTextSyntaxColors"].as_dictionary().unwrap();
+        let fonts = dict["DVTSourceTextSyntaxFonts"].as_dictionary().unwrap();
+
+        assert!(colors.keys().eq(fonts.keys()));
+        assert!(colors.contains_key("xcode.syntax.plain"));
+        for key in [
+            "DVTSourceTextSelectionColor",
+            "DVTSourceTextInsertionPointColor",
+            "DVTConsoleTextBackgroundColor",
+            "DVTConsoleExectuableOutputTextColor",
+        ] {
+            assert!(dict.contains_key(key), "missing {key}");
+        }
+    }
+}
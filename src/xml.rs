@@ -0,0 +1,108 @@
+//! Minimal XML writer for exporters whose formats are plain XML.
+//!
+//! Names are `'static` (fixed by the format); attribute values are escaped on
+//! output, so any palette content yields a well-formed document.
+
+use std::fmt::Write;
+
+/// An element with attributes and child elements.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Element {
+    name: &'static str,
+    attributes: Vec<(&'static str, String)>,
+    children: Vec<Element>,
+}
+
+impl Element {
+    pub(crate) const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            attributes: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    pub(crate) fn attr(mut self, name: &'static str, value: impl Into<String>) -> Self {
+        self.attributes.push((name, value.into()));
+        self
+    }
+
+    #[must_use]
+    pub(crate) fn child(mut self, child: Self) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    pub(crate) fn push(&mut self, child: Self) {
+        self.children.push(child);
+    }
+
+    /// Serialize with two-space indentation and a trailing newline.
+    pub(crate) fn render(&self) -> String {
+        let mut out = String::new();
+        self.write(&mut out, 0);
+        out
+    }
+
+    fn write(&self, out: &mut String, depth: usize) {
+        let indent = "  ".repeat(depth);
+        let _ = write!(out, "{indent}<{}", self.name);
+        for (name, value) in &self.attributes {
+            let _ = write!(out, " {name}=\"{}\"", escape(value));
+        }
+
+        if self.children.is_empty() {
+            out.push_str("/>\n");
+            return;
+        }
+        out.push_str(">\n");
+        for child in &self.children {
+            child.write(out, depth + 1);
+        }
+        let _ = writeln!(out, "{indent}</{}>", self.name);
+    }
+}
+
+/// Escape text for use inside a double-quoted attribute value.
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            '\n' => out.push_str("&#10;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_nested_elements() {
+        let mut root = Element::new("scheme").attr("name", "Akari");
+        root.push(Element::new("colors").child(Element::new("option").attr("value", "25231F")));
+        root.push(Element::new("attributes"));
+
+        assert_eq!(
+            root.render(),
+            "<scheme name=\"Akari\">\n  <colors>\n    <option value=\"25231F\"/>\n  </colors>\n  <attributes/>\n</scheme>\n"
+        );
+    }
+
+    #[test]
+    fn escape_attribute_values() {
+        let element = Element::new("option").attr("name", "a<b & \"c\"\n'd'");
+        assert_eq!(
+            element.render(),
+            "<option name=\"a&lt;b &amp; &quot;c&quot;&#10;&apos;d&apos;\"/>\n"
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use akari_theme::{Generator, Palette, Variant, find_project_root};
+use akari_theme::{Artifact, Exporter, Generator, Palette, Variant, find_project_root};
 use std::path::PathBuf;
 
 fn palette_dir() -> PathBuf {
@@ -19,47 +19,44 @@ mod palette {
     #[test]
     fn load_night() {
         let path = palette_dir().join("akari-night.toml");
-        let palette = Palette::from_path(&path, Variant::Night).unwrap();
-        assert_eq!(palette.variant, Variant::Night);
+        let palette = Palette::from_path(&path, Variant::NIGHT).unwrap();
+        assert_eq!(palette.variant, Variant::NIGHT);
         assert_eq!(palette.name, "akari-night");
     }
 
     #[test]
     fn load_dawn() {
         let path = palette_dir().join("akari-dawn.toml");
-        let palette = Palette::from_path(&path, Variant::Dawn).unwrap();
-        assert_eq!(palette.variant, Variant::Dawn);
+        let palette = Palette::from_path(&path, Variant::DAWN).unwrap();
+        assert_eq!(palette.variant, Variant::DAWN);
         assert_eq!(palette.name, "akari-dawn");
     }
 
     #[test]
-    fn both_palettes_have_valid_hex_colors() {
-        for variant in [Variant::Night, Variant::Dawn] {
+    fn both_palettes_serialize_colors_as_hex() {
+        for variant in [Variant::NIGHT, Variant::DAWN] {
             let path = palette_dir().join(variant.palette_filename());
             let palette = Palette::from_path(&path, variant).unwrap();
 
-            // Check base colors are valid hex
-            assert!(palette.base.background.starts_with('#'));
-            assert!(palette.base.foreground.starts_with('#'));
-            assert_eq!(palette.base.background.len(), 7);
-            assert_eq!(palette.base.foreground.len(), 7);
-
-            // Check semantic colors are resolved to hex
-            assert!(palette.semantic.keyword.starts_with('#'));
-            assert!(palette.semantic.string.starts_with('#'));
-            assert!(palette.semantic.function.starts_with('#'));
-
-            // Check ANSI colors are resolved to hex
-            assert!(palette.ansi.black.starts_with('#'));
-            assert!(palette.ansi.white.starts_with('#'));
-            assert!(palette.ansi_bright.black.starts_with('#'));
+            // Base colors are opaque and serialize as #RRGGBB for templates
+            assert!(palette.base.background.is_opaque());
+            assert!(palette.base.foreground.is_opaque());
+            let base = toml::to_string(&palette.base).unwrap();
+            assert!(base.contains(&format!("background = \"{}\"", palette.base.background)));
+            assert_eq!(palette.base.background.to_string().len(), 7);
+
+            // Semantic and ANSI colors serialize as hex
+            let semantic = toml::to_string(&palette.semantic).unwrap();
+            assert!(semantic.contains("keyword = \"#"));
+            let ansi = toml::to_string(&palette.ansi_bright).unwrap();
+            assert!(ansi.contains("black = \"#"));
         }
     }
 
     #[test]
     fn embedded_night_matches_file() {
         let path = palette_dir().join("akari-night.toml");
-        let from_file = Palette::from_path(&path, Variant::Night).unwrap();
+        let from_file = Palette::from_path(&path, Variant::NIGHT).unwrap();
         let embedded = Palette::night();
 
         assert_eq!(embedded.variant, from_file.variant);
@@ -71,7 +68,7 @@ mod palette {
     #[test]
     fn embedded_dawn_matches_file() {
         let path = palette_dir().join("akari-dawn.toml");
-        let from_file = Palette::from_path(&path, Variant::Dawn).unwrap();
+        let from_file = Palette::from_path(&path, Variant::DAWN).unwrap();
         let embedded = Palette::dawn();
 
         assert_eq!(embedded.variant, from_file.variant);
@@ -84,11 +81,8 @@ mod palette {
 mod generator {
     use super::*;
 
-    fn load_palettes() -> (Palette, Palette) {
-        let dir = palette_dir();
-        let night = Palette::from_path(dir.join("akari-night.toml"), Variant::Night).unwrap();
-        let dawn = Palette::from_path(dir.join("akari-dawn.toml"), Variant::Dawn).unwrap();
-        (night, dawn)
+    fn load_palettes() -> Vec<Palette> {
+        Palette::discover(palette_dir()).unwrap()
     }
 
     #[test]
@@ -101,8 +95,8 @@ mod generator {
     #[test]
     fn generate_helix() {
         let generator = Generator::new(templates_dir()).unwrap();
-        let (night, dawn) = load_palettes();
-        let artifacts = generator.generate_tool("helix", &night, &dawn).unwrap();
+        let palettes = load_palettes();
+        let artifacts = generator.generate_tool("helix", &palettes).unwrap();
 
         assert!(!artifacts.is_empty());
         // Should have at least night and dawn themes
@@ -114,14 +108,48 @@ mod generator {
         assert!(paths.iter().any(|p| p.contains("dawn")));
     }
 
+    #[test]
+    fn discover_palettes() {
+        let palettes = load_palettes();
+        let variants: Vec<_> = palettes.iter().map(|p| p.variant.clone()).collect();
+        assert_eq!(variants, [Variant::DAWN, Variant::NIGHT]);
+    }
+
+    #[test]
+    fn generate_additional_variant() {
+        let generator = Generator::new(templates_dir()).unwrap();
+        let mut palettes = load_palettes();
+        palettes.push(
+            Palette::from_str(
+                "extends = \"akari-night\"\nname = \"akari-dusk\"\n",
+                Variant::new("dusk"),
+            )
+            .unwrap(),
+        );
+
+        let artifacts = generator.generate_tool("helix", &palettes).unwrap();
+        assert!(
+            artifacts
+                .iter()
+                .any(|a| a.rel_path.to_string_lossy().contains("dusk"))
+        );
+
+        let artifacts = generator.generate_tool("terminal", &palettes).unwrap();
+        assert!(
+            artifacts
+                .iter()
+                .any(|a| a.rel_path.ends_with("Akari-Dusk.terminal"))
+        );
+    }
+
     #[test]
     fn generate_all_tools() {
         let generator = Generator::new(templates_dir()).unwrap();
-        let (night, dawn) = load_palettes();
+        let palettes = load_palettes();
         let tools = generator.available_tools().unwrap();
 
         for tool in tools {
-            let result = generator.generate_tool(&tool, &night, &dawn);
+            let result = generator.generate_tool(&tool, &palettes);
             assert!(
                 result.is_ok(),
                 "failed to generate {}: {:?}",
@@ -130,6 +158,118 @@ mod generator {
             );
         }
     }
+
+    struct SwatchExporter;
+
+    impl Exporter for SwatchExporter {
+        fn name(&self) -> &str {
+            "swatches"
+        }
+
+        fn export(&self, palettes: &[Palette]) -> Result<Vec<Artifact>, akari_theme::Error> {
+            Ok(palettes
+                .iter()
+                .map(|p| {
+                    Artifact::text(
+                        format!("{}.txt", p.variant.name()),
+                        p.base.background.to_string(),
+                    )
+                })
+                .collect())
+        }
+    }
+
+    #[test]
+    fn builtin_exporters_are_tools() {
+        let generator = Generator::new(templates_dir()).unwrap();
+        let tools = generator.available_tools().unwrap();
+        assert!(tools.contains(&"terminal".to_string()));
+        assert!(tools.contains(&"iterm2".to_string()));
+        assert!(tools.is_sorted());
+    }
+
+    #[test]
+    fn register_custom_exporter() {
+        let mut generator = Generator::new(templates_dir()).unwrap();
+        generator.register(SwatchExporter);
+        assert!(
+            generator
+                .available_tools()
+                .unwrap()
+                .contains(&"swatches".to_string())
+        );
+
+        let artifacts = generator
+            .generate_tool("swatches", &load_palettes())
+            .unwrap();
+        let paths: Vec<_> = artifacts.iter().map(|a| a.rel_path.clone()).collect();
+        assert_eq!(
+            paths,
+            [
+                PathBuf::from("swatches/dawn.txt"),
+                PathBuf::from("swatches/night.txt")
+            ]
+        );
+    }
+
+    #[test]
+    fn generate_tools_in_parallel() {
+        let generator = Generator::new(templates_dir()).unwrap();
+        let palettes = load_palettes();
+        let tools = generator.available_tools().unwrap();
+
+        let sequential: Vec<_> = tools
+            .iter()
+            .flat_map(|tool| generator.generate_tool(tool, &palettes).unwrap())
+            .map(|a| a.rel_path)
+            .collect();
+        let parallel: Vec<_> = generator
+            .generate_tools(&tools, &palettes)
+            .unwrap()
+            .into_iter()
+            .map(|a| a.rel_path)
+            .collect();
+        assert_eq!(parallel, sequential);
+    }
+
+    struct ConstExporter(&'static str);
+
+    impl Exporter for ConstExporter {
+        fn name(&self) -> &str {
+            "const"
+        }
+
+        fn export(&self, _palettes: &[Palette]) -> Result<Vec<Artifact>, akari_theme::Error> {
+            Ok(vec![Artifact::text("out.txt", self.0)])
+        }
+    }
+
+    #[test]
+    fn fingerprint_tracks_exporter_output() {
+        let palettes = load_palettes();
+        let fingerprint = |output| {
+            let mut generator = Generator::new(templates_dir()).unwrap();
+            generator.register(ConstExporter(output));
+            generator.fingerprint("const", &palettes).unwrap()
+        };
+
+        assert_eq!(fingerprint("a"), fingerprint("a"));
+        assert_ne!(fingerprint("a"), fingerprint("b"));
+    }
+
+    #[test]
+    fn fingerprint_tracks_palettes() {
+        let generator = Generator::new(templates_dir()).unwrap();
+        let palettes = load_palettes();
+        let hash = generator.fingerprint("helix", &palettes).unwrap();
+
+        assert_eq!(generator.fingerprint("helix", &palettes).unwrap(), hash);
+        assert_ne!(generator.fingerprint("zsh", &palettes).unwrap(), hash);
+        assert_ne!(
+            generator.fingerprint("helix", &palettes[..1]).unwrap(),
+            hash
+        );
+    }
 }
 
 mod terminal {
@@ -138,7 +278,7 @@ mod terminal {
     #[test]
     fn generate_night() {
         let path = palette_dir().join("akari-night.toml");
-        let palette = Palette::from_path(&path, Variant::Night).unwrap();
+        let palette = Palette::from_path(&path, Variant::NIGHT).unwrap();
         let content = akari_theme::terminal::generate(&palette).unwrap();
 
         assert!(content.contains("<?xml"));
@@ -151,9 +291,23 @@ mod terminal {
     #[test]
     fn generate_dawn() {
         let path = palette_dir().join("akari-dawn.toml");
-        let palette = Palette::from_path(&path, Variant::Dawn).unwrap();
+        let palette = Palette::from_path(&path, Variant::DAWN).unwrap();
         let content = akari_theme::terminal::generate(&palette).unwrap();
 
         assert!(content.contains("Akari-Dawn"));
     }
 }
+
+mod schema {
+    use super::*;
+
+    #[test]
+    fn committed_schema_is_current() {
+        let committed = std::fs::read_to_string(palette_dir().join("schema.json")).unwrap();
+        let generated = format!("{:#}\n", akari_theme::palette_schema());
+        assert_eq!(
+            committed, generated,
+            "run `cargo run --features generator -- schema -o palette/schema.json`"
+        );
+    }
+}